@@ -0,0 +1,349 @@
+use crate::types::{ColSelector, parse_col_selector};
+use crate::values::{CellType, parse_number};
+use csv::StringRecord;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Function {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim() {
+            "count" => Ok(Self::Count),
+            "sum" => Ok(Self::Sum),
+            "avg" => Ok(Self::Avg),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            other => Err(format!(
+                "unknown function \"{other}\" (supported: count, sum, avg, min, max)"
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+
+    /// Computes the function over a column's cells, ignoring empty ones; `label` identifies the
+    /// computation in errors.
+    pub fn compute(self, cells: &[&str], label: &str) -> anyhow::Result<String> {
+        let cells = cells
+            .iter()
+            .copied()
+            .filter(|c| !c.trim().is_empty())
+            .collect::<Vec<_>>();
+
+        let numbers = || {
+            cells
+                .iter()
+                .map(|c| {
+                    parse_number(c).ok_or_else(|| {
+                        anyhow::anyhow!("couldn't compute {label}: \"{c}\" is not a number")
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+
+        let value = match self {
+            Self::Count => cells.len().to_string(),
+            Self::Sum => format_number(numbers()?.iter().sum()),
+            Self::Avg => {
+                let numbers = numbers()?;
+                if numbers.is_empty() {
+                    String::new()
+                } else {
+                    format_number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+                }
+            }
+            Self::Min | Self::Max => {
+                let cell_type = CellType::infer(cells.iter().copied());
+                let ordered = |a: &&&str, b: &&&str| cell_type.compare(a, b, false);
+                let value = if self == Self::Min {
+                    cells.iter().min_by(ordered)
+                } else {
+                    cells.iter().max_by(ordered)
+                };
+                value.map(|v| v.to_string()).unwrap_or_default()
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+/// An aggregate computed over the rows of each group, as passed to --agg (eg. `avg(Year)`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    function: Function,
+    /// Column to aggregate; a plain `count` counts rows, and doesn't need one
+    col: Option<ColSelector>,
+    label: String,
+}
+
+impl Aggregate {
+    /// Counts the rows in each group.
+    pub fn count() -> Self {
+        Self {
+            function: Function::Count,
+            col: None,
+            label: "count".to_string(),
+        }
+    }
+}
+
+pub fn parse_aggregate(value: &str) -> Result<Aggregate, String> {
+    let value = value.trim();
+    if value == "count" {
+        return Ok(Aggregate::count());
+    }
+
+    let Some((name, col)) = value.strip_suffix(')').and_then(|v| v.split_once('(')) else {
+        return Err(
+            "needs to be either count, or a function applied to a column (eg. avg(Year))"
+                .to_string(),
+        );
+    };
+
+    let function = Function::parse(name)?;
+
+    let col = parse_col_selector(col.trim())?;
+    if let ColSelector::Range(..) = col {
+        return Err("aggregates can't be computed over a range of columns".to_string());
+    }
+
+    Ok(Aggregate {
+        function,
+        col: Some(col),
+        label: value.to_string(),
+    })
+}
+
+/// Collapses rows into one row per distinct combination of values in the group-by columns (in
+/// the order they first appear), followed by the aggregates computed over each group's rows. The
+/// first record is expected to be the header.
+pub fn group_rows(
+    records: Vec<StringRecord>,
+    group_by: &[ColSelector],
+    aggregates: &[Aggregate],
+) -> anyhow::Result<Vec<StringRecord>> {
+    let mut records = records.into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let rows = records.collect::<Vec<_>>();
+    let width = rows
+        .iter()
+        .map(StringRecord::len)
+        .max()
+        .unwrap_or_default()
+        .max(header.len());
+
+    let mut key_indices = Vec::new();
+    for selector in group_by {
+        key_indices.extend(selector.resolve(Some(&header), width)?);
+    }
+
+    let mut agg_indices = Vec::new();
+    for aggregate in aggregates {
+        let index = match &aggregate.col {
+            Some(col) => col.resolve(Some(&header), width)?.first().copied(),
+            None => None,
+        };
+        agg_indices.push(index);
+    }
+
+    let mut groups: Vec<(Vec<&str>, Vec<&StringRecord>)> = Vec::new();
+    let mut group_positions: HashMap<Vec<&str>, usize> = HashMap::new();
+    for row in &rows {
+        let key = key_indices
+            .iter()
+            .map(|i| row.get(*i).unwrap_or_default())
+            .collect::<Vec<_>>();
+        match group_positions.get(&key) {
+            Some(position) => groups[*position].1.push(row),
+            None => {
+                group_positions.insert(key.clone(), groups.len());
+                groups.push((key, vec![row]));
+            }
+        }
+    }
+
+    // without group-by columns, every row belongs to a single group, even when there are none
+    if group_by.is_empty() && groups.is_empty() {
+        groups.push((Vec::new(), Vec::new()));
+    }
+
+    let mut grouped_header = key_indices
+        .iter()
+        .map(|i| header.get(*i).unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    grouped_header.extend(aggregates.iter().map(|a| a.label.clone()));
+
+    let mut grouped = vec![StringRecord::from(grouped_header)];
+    for (key, group) in groups {
+        let mut record = StringRecord::from(key);
+        for (aggregate, index) in aggregates.iter().zip(agg_indices.iter()) {
+            let value = match index {
+                Some(index) => {
+                    let cells = group
+                        .iter()
+                        .filter_map(|r| r.get(*index))
+                        .collect::<Vec<_>>();
+                    aggregate.function.compute(&cells, &aggregate.label)?
+                }
+                None if aggregate.col.is_none() => group.len().to_string(),
+                // the column is a negative index that points before the first one
+                None => String::new(),
+            };
+            record.push_field(&value);
+        }
+        grouped.push(record);
+    }
+
+    Ok(grouped)
+}
+
+/// Formats a number with at most 4 decimal places, dropping trailing zeros.
+pub fn format_number(number: f64) -> String {
+    let formatted = format!("{number:.4}");
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+
+    match formatted {
+        "-0" => "0".to_string(),
+        f => f.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    fn generate_data() -> Vec<StringRecord> {
+        vec![
+            StringRecord::from(vec!["Movie", "Year", "Genre"]),
+            StringRecord::from(vec!["The Matrix", "1999", "Science Fiction"]),
+            StringRecord::from(vec!["Pulp Fiction", "1994", "Crime"]),
+            StringRecord::from(vec!["The Shawshank Redemption", "1994", "Drama"]),
+            StringRecord::from(vec!["Heat", "1995", "Crime"]),
+            StringRecord::from(vec!["Fargo", "", "Crime"]),
+        ]
+    }
+
+    #[test]
+    fn aggregates_rows_per_group() {
+        // GIVEN
+        let aggregates = [
+            "count",
+            "count(Year)",
+            "avg(Year)",
+            "min(Movie)",
+            "max(Year)",
+        ]
+        .into_iter()
+        .map(|a| parse_aggregate(a).expect("aggregate should've been parsed"))
+        .collect::<Vec<_>>();
+        let group_by = vec![ColSelector::Name("Genre".to_string())];
+
+        // WHEN
+        let got = group_rows(generate_data(), &group_by, &aggregates)
+            .expect("rows should've been grouped");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec![
+                    "Genre",
+                    "count",
+                    "count(Year)",
+                    "avg(Year)",
+                    "min(Movie)",
+                    "max(Year)"
+                ],
+                vec!["Science Fiction", "1", "1", "1999", "The Matrix", "1999"],
+                vec!["Crime", "3", "2", "1994.5", "Fargo", "1995"],
+                vec![
+                    "Drama",
+                    "1",
+                    "1",
+                    "1994",
+                    "The Shawshank Redemption",
+                    "1994"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregates_all_rows_without_group_by_columns() {
+        // GIVEN
+        let aggregates =
+            vec![parse_aggregate("sum(Year)").expect("aggregate should've been parsed")];
+
+        // WHEN
+        let got =
+            group_rows(generate_data(), &[], &aggregates).expect("rows should've been grouped");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["sum(Year)"], vec!["7982"]]);
+    }
+
+    #[test]
+    fn aggregates_empty_input_without_group_by_columns() {
+        // GIVEN
+        let records = vec![StringRecord::from(vec!["Movie", "Year"])];
+        let aggregates = ["count", "sum(Year)", "avg(Year)"]
+            .into_iter()
+            .map(|a| parse_aggregate(a).expect("aggregate should've been parsed"))
+            .collect::<Vec<_>>();
+
+        // WHEN
+        let got = group_rows(records, &[], &aggregates).expect("rows should've been grouped");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![vec!["count", "sum(Year)", "avg(Year)"], vec!["0", "0", ""]]
+        );
+    }
+
+    #[test]
+    fn fails_to_average_text() {
+        // GIVEN
+        let aggregates =
+            vec![parse_aggregate("avg(Genre)").expect("aggregate should've been parsed")];
+
+        // WHEN
+        let got = group_rows(generate_data(), &[], &aggregates);
+
+        // THEN
+        assert_eq!(
+            got.expect_err("rows shouldn't have been grouped")
+                .to_string(),
+            r#"couldn't compute avg(Genre): "Science Fiction" is not a number"#
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_functions() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert!(parse_aggregate("median(Year)").is_err());
+        assert!(parse_aggregate("avg").is_err());
+        assert!(parse_aggregate("sum(0-2)").is_err());
+    }
+}
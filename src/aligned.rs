@@ -0,0 +1,212 @@
+use crate::errors::{RowErrors, get_line_read_error};
+use csv::StringRecord;
+use std::io::BufRead;
+
+/// Parses column-aligned text (like the output of `ps`, `docker ps`, or `df`) where the first
+/// non-empty line is a header, and column boundaries are inferred from the header's words and
+/// the runs of spaces shared by every line.
+pub fn get_records_from_aligned_text<R: BufRead>(
+    reader: R,
+    row_errors: &mut RowErrors,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let mut lines = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                row_errors.handle(get_line_read_error(e, i as u64 + 1))?;
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        lines.push(line.trim_end().chars().collect::<Vec<char>>());
+    }
+
+    let starts = get_column_starts(&lines);
+
+    Ok(lines
+        .iter()
+        .map(|line| StringRecord::from(split_line(line, &starts)))
+        .collect())
+}
+
+fn get_column_starts(lines: &[Vec<char>]) -> Vec<usize> {
+    let Some(header) = lines.first() else {
+        return Vec::new();
+    };
+
+    let width = lines.iter().map(Vec::len).max().unwrap_or_default();
+    let is_gap = (0..width)
+        .map(|i| {
+            lines
+                .iter()
+                .all(|line| line.get(i).is_none_or(|c| c.is_whitespace()))
+        })
+        .collect::<Vec<_>>();
+
+    let words = get_words(header);
+    let mut starts = vec![0];
+    for (i, pair) in words.windows(2).enumerate() {
+        let (prev_end, start) = (pair[0].1, pair[1].0);
+        // the rightmost gap before a header word marks where its column begins; right-aligned
+        // values can extend to the left of the header word itself
+        let Some(gap) = (prev_end..start).rev().find(|i| is_gap[*i]) else {
+            continue;
+        };
+
+        // a header word separated by a single space whose column holds no data (eg. "Mounted on"
+        // in the output of `df`) is considered to be a part of the previous header
+        let next_start = words.get(i + 2).map_or(width, |w| w.0);
+        if start - prev_end == 1 && is_region_empty(&lines[1..], gap, next_start) {
+            continue;
+        }
+
+        starts.push(gap);
+    }
+
+    starts
+}
+
+/// Returns (start, end) char positions of whitespace separated words.
+fn get_words(line: &[char]) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in line.iter().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, line.len()));
+    }
+
+    words
+}
+
+fn is_region_empty(rows: &[Vec<char>], start: usize, end: usize) -> bool {
+    rows.iter()
+        .all(|row| row.iter().take(end).skip(start).all(|c| c.is_whitespace()))
+}
+
+fn split_line(line: &[char], starts: &[usize]) -> Vec<String> {
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let end = starts
+                .get(i + 1)
+                .copied()
+                .unwrap_or(line.len())
+                .min(line.len());
+            line.get(*start..end)
+                .map(|cell| cell.iter().collect::<String>().trim().to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RowErrorPolicy;
+    use crate::test_utils::to_rows;
+
+    #[test]
+    fn keeps_multi_word_headers_together() {
+        // GIVEN
+        let input = "\
+CONTAINER ID   IMAGE          CREATED        STATUS
+a1b2c3d4e5f6   nginx:latest   2 hours ago    Up 2 hours
+f6e5d4c3b2a1   redis          3 days ago     Exited (0) 2 days ago
+";
+
+        // WHEN
+        let got = get_records_from_aligned_text(
+            input.as_bytes(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["CONTAINER ID", "IMAGE", "CREATED", "STATUS"],
+                vec!["a1b2c3d4e5f6", "nginx:latest", "2 hours ago", "Up 2 hours"],
+                vec![
+                    "f6e5d4c3b2a1",
+                    "redis",
+                    "3 days ago",
+                    "Exited (0) 2 days ago"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_right_aligned_and_single_spaced_columns() {
+        // GIVEN
+        let input = "\
+USER         PID %CPU %MEM COMMAND
+root           1  0.0  0.1 /sbin/init splash
+postgres  123456 12.5  3.2 postgres: writer process
+";
+
+        // WHEN
+        let got = get_records_from_aligned_text(
+            input.as_bytes(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["USER", "PID", "%CPU", "%MEM", "COMMAND"],
+                vec!["root", "1", "0.0", "0.1", "/sbin/init splash"],
+                vec![
+                    "postgres",
+                    "123456",
+                    "12.5",
+                    "3.2",
+                    "postgres: writer process"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_single_spaced_header_words_without_data() {
+        // GIVEN
+        let input = "\
+Filesystem      Size  Used Avail Use% Mounted on
+/dev/sda1        50G   20G   28G  42% /
+tmpfs           7.8G     0  7.8G   0% /dev/shm
+";
+
+        // WHEN
+        let got = get_records_from_aligned_text(
+            input.as_bytes(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["Filesystem", "Size", "Used", "Avail", "Use%", "Mounted on"],
+                vec!["/dev/sda1", "50G", "20G", "28G", "42%", "/"],
+                vec!["tmpfs", "7.8G", "0", "7.8G", "0%", "/dev/shm"],
+            ]
+        );
+    }
+}
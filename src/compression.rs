@@ -0,0 +1,203 @@
+use anyhow::Context;
+use std::io::{BufRead, Read};
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+// a bzip2 stream starts with either a block, or (if it's empty) the end of the stream
+const BZIP2_BLOCK_MAGICS: [&[u8]; 2] = [
+    &[0x31, 0x41, 0x59, 0x26, 0x53, 0x59],
+    &[0x17, 0x72, 0x45, 0x38, 0x50, 0x90],
+];
+const XZ_MAGIC: &[u8] = &[0xFD, b'7', b'z', b'X', b'Z', 0x00];
+// long enough for any of the magic numbers above, the longest being bzip2's (with its block size
+// digit and block magic)
+const HEADER_LEN: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    fn detect(header: &[u8], path: Option<&str>) -> Option<Self> {
+        let from_magic = [
+            (GZIP_MAGIC, Self::Gzip),
+            (ZSTD_MAGIC, Self::Zstd),
+            (XZ_MAGIC, Self::Xz),
+        ]
+        .into_iter()
+        .find(|(magic, _)| header.starts_with(magic))
+        .map(|(_, compression)| compression)
+        .or_else(|| is_bzip2(header).then_some(Self::Bzip2));
+
+        from_magic.or_else(|| {
+            let extension = path?.rsplit_once('.')?.1;
+            match extension {
+                "gz" => Some(Self::Gzip),
+                "zst" => Some(Self::Zstd),
+                "bz2" => Some(Self::Bzip2),
+                "xz" => Some(Self::Xz),
+                _ => None,
+            }
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Bzip2 => "bzip2",
+            Self::Xz => "xz",
+        }
+    }
+}
+
+/// "BZh" alone is too likely to show up at the start of plain text, so the block size digit, and
+/// the magic number that follows it are checked as well.
+fn is_bzip2(header: &[u8]) -> bool {
+    match header.strip_prefix(BZIP2_MAGIC) {
+        Some([b'1'..=b'9', rest @ ..]) => BZIP2_BLOCK_MAGICS.iter().any(|m| rest.starts_with(m)),
+        _ => false,
+    }
+}
+
+/// Reads the input to the end, decompressing it on the fly if it's compressed (as indicated by
+/// its magic bytes, or the extension of the file it's read from).
+pub fn read_input<R: BufRead>(mut reader: R, path: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    // a single read can come up short (eg. when reading from a pipe), so the header is read in full
+    // before it's handed back along with the rest of the input
+    let mut header = Vec::new();
+    reader.by_ref().take(HEADER_LEN).read_to_end(&mut header)?;
+    let Some(compression) = Compression::detect(&header, path) else {
+        let mut input = header;
+        reader.read_to_end(&mut input)?;
+        return Ok(input);
+    };
+
+    decompress(header.as_slice().chain(reader), compression)
+        .with_context(|| format!("couldn't decompress {} input", compression.name()))
+}
+
+fn decompress<R: BufRead>(mut reader: R, compression: Compression) -> anyhow::Result<Vec<u8>> {
+    let mut input = Vec::new();
+    match compression {
+        Compression::Gzip => {
+            flate2::bufread::MultiGzDecoder::new(reader).read_to_end(&mut input)?;
+        }
+        Compression::Zstd => {
+            ruzstd::decoding::StreamingDecoder::new(reader)?.read_to_end(&mut input)?;
+        }
+        Compression::Bzip2 => {
+            bzip2_rs::DecoderReader::new(reader).read_to_end(&mut input)?;
+        }
+        Compression::Xz => lzma_rs::xz_decompress(&mut reader, &mut input)?,
+    }
+
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Write};
+
+    const INPUT: &[u8] = b"Movie,Year\nThe Matrix,1999\n";
+
+    /// Hands out a single byte per read, like a pipe that's written to slowly.
+    struct TrickleReader<'a>(&'a [u8]);
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn leaves_uncompressed_input_untouched() {
+        // GIVEN
+        // WHEN
+        let got = read_input(INPUT, Some("movies.csv")).expect("input should've been read");
+
+        // THEN
+        assert_eq!(got, INPUT);
+    }
+
+    #[test]
+    fn decompresses_gzip_input() {
+        // GIVEN
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(INPUT)
+            .expect("input should've been compressed");
+        let compressed = encoder.finish().expect("input should've been compressed");
+
+        // WHEN
+        let got = read_input(compressed.as_slice(), None).expect("input should've been read");
+
+        // THEN
+        assert_eq!(got, INPUT);
+    }
+
+    #[test]
+    fn detects_compression_of_input_read_a_byte_at_a_time() {
+        // GIVEN
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(INPUT)
+            .expect("input should've been compressed");
+        let compressed = encoder.finish().expect("input should've been compressed");
+        let reader = BufReader::new(TrickleReader(&compressed));
+
+        // WHEN
+        let got = read_input(reader, None).expect("input should've been read");
+
+        // THEN
+        assert_eq!(got, INPUT);
+    }
+
+    #[test]
+    fn detects_compression_from_extension() {
+        // GIVEN
+        // WHEN
+        let got = read_input(INPUT, Some("movies.csv.zst"));
+
+        // THEN
+        assert_eq!(
+            got.expect_err("input shouldn't have been read").to_string(),
+            "couldn't decompress zstd input"
+        );
+    }
+
+    #[test]
+    fn leaves_plain_text_resembling_bzip2_untouched() {
+        // GIVEN
+        let input = b"BZh9,Year\n1,1999\n".as_slice();
+
+        // WHEN
+        let got = read_input(input, None).expect("input should've been read");
+
+        // THEN
+        assert_eq!(got, input);
+    }
+
+    #[test]
+    fn decompresses_bzip2_input() {
+        // GIVEN
+        let compressed = std::fs::read("tests/data/input-compressed.txt.bz2")
+            .expect("compressed input should've been read");
+        let expected = std::fs::read("tests/data/input-1.txt").expect("input should've been read");
+
+        // WHEN
+        let got = read_input(compressed.as_slice(), None).expect("input should've been read");
+
+        // THEN
+        assert_eq!(got, expected);
+    }
+}
@@ -18,6 +18,60 @@ pub enum TableStyle {
     Sharp,
 }
 
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum InputFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Aligned,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Auto,
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-16le")]
+    Utf16le,
+    #[value(name = "utf-16be")]
+    Utf16be,
+    Latin1,
+    #[value(name = "windows-1252")]
+    Windows1252,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum ArrayMode {
+    Join,
+    Index,
+    Json,
+}
+
+pub struct FlattenConfig {
+    pub depth: Option<usize>,
+    pub array_mode: ArrayMode,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum ExpandedMode {
+    Always,
+    Auto,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum RaggedRowPolicy {
+    Pad,
+    Truncate,
+    Error,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum RowErrorPolicy {
+    Skip,
+    Warn,
+    Fail,
+}
+
 pub struct TablePadding {
     pub left: usize,
     pub right: usize,
@@ -26,6 +80,9 @@ pub struct TablePadding {
 pub struct RenderConfig {
     pub style: TableStyle,
     pub padding: TablePadding,
+    pub header: bool,
+    /// Number of rows at the bottom that make up the footer
+    pub footer_rows: usize,
 }
 
 impl TableStyle {
@@ -0,0 +1,586 @@
+use crate::config::RowErrorPolicy;
+use crate::errors::{InputError, RowErrors};
+use anyhow::Context;
+use csv::StringRecord;
+use regex::Regex;
+
+const DELIMITER_CANDIDATES: [u8; 5] = [b',', b'\t', b';', b'|', b':'];
+const FALLBACK_DELIMITER: u8 = b',';
+const NUM_LINES_TO_SNIFF: usize = 20;
+// multi-character delimiters are swapped with this (the ASCII unit separator) before the input is
+// handed to the csv reader
+const PLACEHOLDER: u8 = 0x1f;
+
+/// The csv dialect knobs (other than the delimiter) exposed via the CLI.
+pub struct Dialect {
+    pub quote: u8,
+    pub escape: Option<u8>,
+    pub double_quote: bool,
+    pub comment: Option<u8>,
+    pub terminator: Option<u8>,
+    /// Whether rows are allowed to have a different number of fields than the first one
+    pub flexible: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            quote: b'"',
+            escape: None,
+            double_quote: true,
+            comment: None,
+            terminator: None,
+            flexible: false,
+        }
+    }
+}
+
+impl Dialect {
+    fn reader_builder(&self, delimiter: u8) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .delimiter(delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .flexible(self.flexible)
+            .terminator(
+                self.terminator
+                    .map_or(csv::Terminator::CRLF, csv::Terminator::Any),
+            );
+
+        builder
+    }
+}
+
+pub enum Delimiter {
+    Byte(u8),
+    Literal(String),
+    Regex(Regex),
+}
+
+impl Delimiter {
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let value = unescape(value)?;
+        if value.is_empty() {
+            anyhow::bail!("delimiter cannot be empty");
+        }
+
+        if value.contains(['\n', '\r']) {
+            anyhow::bail!("delimiter cannot contain line breaks");
+        }
+
+        match value.as_bytes() {
+            [byte] => Ok(Self::Byte(*byte)),
+            _ => Ok(Self::Literal(value)),
+        }
+    }
+
+    pub fn parse_regex(value: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(value).context("delimiter regex is invalid")?;
+        if regex.is_match("") {
+            anyhow::bail!("delimiter regex cannot match an empty string");
+        }
+
+        Ok(Self::Regex(regex))
+    }
+
+    /// Whether the delimiter contains the given byte; regex delimiters never do, as they're applied
+    /// without a csv reader.
+    pub fn contains(&self, byte: u8) -> bool {
+        match self {
+            Self::Byte(b) => *b == byte,
+            Self::Literal(literal) => literal.as_bytes().contains(&byte),
+            Self::Regex(_) => false,
+        }
+    }
+}
+
+pub fn parse_ascii_char(value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        [byte] if byte.is_ascii() => Ok(*byte),
+        _ => Err("needs to be a single ASCII character".to_string()),
+    }
+}
+
+/// Turns `\t` into a tab and `\\` into a backslash, as passing an actual tab on the command line
+/// requires shell-specific quoting (eg. `$'\t'`).
+fn unescape(value: &str) -> anyhow::Result<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => anyhow::bail!(
+                r"delimiter contains unsupported escape sequence \{other} (only \t and \\ are supported)"
+            ),
+            None => anyhow::bail!(r"delimiter cannot end with a lone backslash (use \\ instead)"),
+        }
+    }
+
+    Ok(unescaped)
+}
+
+pub fn get_records_from_delimited_text(
+    input: &[u8],
+    delimiter: &Delimiter,
+    dialect: &Dialect,
+    row_errors: &mut RowErrors,
+) -> anyhow::Result<Vec<StringRecord>> {
+    match delimiter {
+        Delimiter::Byte(byte) => read_records(input, *byte, dialect, row_errors),
+        Delimiter::Literal(literal) => {
+            if input.contains(&PLACEHOLDER) {
+                anyhow::bail!(
+                    "input contains the ASCII unit separator character, which is needed to support multi-character delimiters"
+                );
+            }
+
+            let replaced = replace_all(input, literal.as_bytes(), PLACEHOLDER);
+            let placeholder = char::from(PLACEHOLDER).to_string();
+
+            // byte offsets in the replaced input don't line up with the original one
+            let records =
+                read_records(&replaced, PLACEHOLDER, dialect, row_errors).map_err(|e| {
+                    match e.downcast::<InputError>() {
+                        Ok(mut input_error) => {
+                            input_error.byte = None;
+                            input_error.into()
+                        }
+                        Err(e) => e,
+                    }
+                })?;
+
+            // delimiters inside quoted fields get replaced as well, so they need to be restored
+            Ok(records
+                .iter()
+                .map(|record| {
+                    StringRecord::from(
+                        record
+                            .iter()
+                            .map(|field| field.replace(&placeholder, literal))
+                            .collect::<Vec<String>>(),
+                    )
+                })
+                .collect())
+        }
+        Delimiter::Regex(regex) => {
+            let mut records: Vec<StringRecord> = Vec::new();
+            let mut byte = 0;
+            for (i, line) in input.split(|b| *b == b'\n').enumerate() {
+                let line_num = i as u64 + 1;
+                let line_start = byte;
+                byte += line.len() as u64 + 1;
+
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                let is_comment = dialect
+                    .comment
+                    .is_some_and(|comment| line.starts_with(&[comment]));
+                if line.is_empty() || is_comment {
+                    continue;
+                }
+
+                let Ok(line) = std::str::from_utf8(line) else {
+                    row_errors.handle(
+                        InputError::new(line_num, "line is not valid UTF-8")
+                            .with_byte(line_start)
+                            .into(),
+                    )?;
+                    continue;
+                };
+
+                let record = StringRecord::from(regex.split(line).collect::<Vec<_>>());
+                if let Some(first) = records.first()
+                    && !dialect.flexible
+                    && first.len() != record.len()
+                {
+                    row_errors.handle(
+                        InputError::new(
+                            line_num,
+                            get_unequal_lengths_message(first.len() as u64, record.len() as u64),
+                        )
+                        .with_byte(line_start)
+                        .into(),
+                    )?;
+                    continue;
+                }
+                records.push(record);
+            }
+
+            Ok(records)
+        }
+    }
+}
+
+/// Parses a single row of delimited text (like the value of `--headers`).
+pub fn parse_delimited_line(
+    line: &str,
+    delimiter: &Delimiter,
+    dialect: &Dialect,
+) -> anyhow::Result<StringRecord> {
+    let mut row_errors = RowErrors::new(RowErrorPolicy::Fail);
+    let records =
+        get_records_from_delimited_text(line.as_bytes(), delimiter, dialect, &mut row_errors)?;
+
+    match records.as_slice() {
+        [record] => Ok(record.clone()),
+        [] => anyhow::bail!("expected a row, found nothing"),
+        _ => anyhow::bail!("expected a single row, found {}", records.len()),
+    }
+}
+
+fn read_records(
+    input: &[u8],
+    delimiter: u8,
+    dialect: &Dialect,
+    row_errors: &mut RowErrors,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let mut records = Vec::new();
+    for result in dialect
+        .reader_builder(delimiter)
+        .from_reader(input)
+        .records()
+    {
+        match result {
+            Ok(record) => records.push(record),
+            Err(e) => row_errors.handle(get_input_error(e))?,
+        }
+    }
+
+    Ok(records)
+}
+
+fn get_input_error(error: csv::Error) -> anyhow::Error {
+    match error.kind() {
+        csv::ErrorKind::UnequalLengths {
+            pos: Some(pos),
+            expected_len,
+            len,
+        } => InputError::new(pos.line(), get_unequal_lengths_message(*expected_len, *len))
+            .with_byte(pos.byte())
+            .into(),
+        csv::ErrorKind::Utf8 {
+            pos: Some(pos),
+            err,
+        } => InputError::new(
+            pos.line(),
+            format!("field {} is not valid UTF-8", err.field() + 1),
+        )
+        .with_byte(pos.byte())
+        .into(),
+        _ => error.into(),
+    }
+}
+
+fn get_unequal_lengths_message(expected_len: u64, len: u64) -> String {
+    format!(
+        "found {len} field(s), but {expected_len} were expected (use --flexible to allow rows with a differing number of fields)"
+    )
+}
+
+fn replace_all(input: &[u8], from: &[u8], to: u8) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i..].starts_with(from) {
+            replaced.push(to);
+            i += from.len();
+        } else {
+            replaced.push(input[i]);
+            i += 1;
+        }
+    }
+
+    replaced
+}
+
+/// Picks the candidate delimiter that splits the first few lines of the input into the same
+/// (and the largest) number of fields, falling back to a comma if none of them do.
+pub fn detect_delimiter(input: &[u8], dialect: &Dialect) -> u8 {
+    let (sample, truncated) = get_sample(input);
+
+    let mut best: Option<(u8, usize)> = None;
+    for delimiter in DELIMITER_CANDIDATES {
+        let mut reader = dialect
+            .reader_builder(delimiter)
+            .flexible(true)
+            .from_reader(sample);
+
+        let Ok(mut records) = reader.records().collect::<Result<Vec<StringRecord>, _>>() else {
+            continue;
+        };
+
+        // the last record might have been cut short by the sample
+        if truncated && records.len() > 1 {
+            records.pop();
+        }
+
+        let Some(num_fields) = records.first().map(StringRecord::len) else {
+            continue;
+        };
+
+        if num_fields < 2 || records.iter().any(|r| r.len() != num_fields) {
+            continue;
+        }
+
+        if best.is_none_or(|(_, n)| num_fields > n) {
+            best = Some((delimiter, num_fields));
+        }
+    }
+
+    best.map_or(FALLBACK_DELIMITER, |(delimiter, _)| delimiter)
+}
+
+fn get_sample(input: &[u8]) -> (&[u8], bool) {
+    match input
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(NUM_LINES_TO_SNIFF - 1)
+    {
+        Some((i, _)) => (&input[..=i], i + 1 < input.len()),
+        None => (input, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    #[test]
+    fn splits_on_multi_character_delimiters() {
+        // GIVEN
+        let input = "a::b\n\"x::y\"::z\n".as_bytes();
+        let delimiter = Delimiter::parse("::").expect("delimiter should've been parsed");
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &delimiter,
+            &Dialect::default(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a", "b"], vec!["x::y", "z"]]);
+    }
+
+    #[test]
+    fn splits_on_non_ascii_delimiters() {
+        // GIVEN
+        let input = "a│b\nc│d\n".as_bytes();
+        let delimiter = Delimiter::parse("│").expect("delimiter should've been parsed");
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &delimiter,
+            &Dialect::default(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn splits_on_regex_delimiters() {
+        // GIVEN
+        let input = "a  b c   d\n\ne    f  g\n".as_bytes();
+        let delimiter = Delimiter::parse_regex(r"\s{2,}").expect("delimiter should've been parsed");
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &delimiter,
+            &Dialect::default(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![vec!["a", "b c", "d"], vec!["e", "f", "g"]]
+        );
+    }
+
+    #[test]
+    fn respects_dialect() {
+        // GIVEN
+        let input = "# exported on monday\n'a;b',c\n'x\\'y',z\n".as_bytes();
+        let dialect = Dialect {
+            quote: b'\'',
+            escape: Some(b'\\'),
+            double_quote: false,
+            comment: Some(b'#'),
+            terminator: None,
+            flexible: false,
+        };
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &Delimiter::Byte(b','),
+            &dialect,
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a;b", "c"], vec!["x'y", "z"]]);
+    }
+
+    #[test]
+    fn reports_line_number_for_rows_with_unequal_lengths() {
+        // GIVEN
+        let input = "a,b\nc,d\ne\n".as_bytes();
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &Delimiter::Byte(b','),
+            &Dialect::default(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect_err("input shouldn't have been parsed");
+
+        // THEN
+        let got = got
+            .downcast::<InputError>()
+            .expect("error should've been an input error");
+        assert_eq!(got.line, 3);
+        assert_eq!(got.byte, Some(8));
+    }
+
+    #[test]
+    fn skips_malformed_rows_when_asked_to() {
+        // GIVEN
+        let input = b"a,b\nc\nd,\xff\ne,f\n";
+        let mut row_errors = RowErrors::new(RowErrorPolicy::Skip);
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &Delimiter::Byte(b','),
+            &Dialect::default(),
+            &mut row_errors,
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a", "b"], vec!["e", "f"]]);
+        assert_eq!(row_errors.skipped_lines(), vec![2, 3]);
+    }
+
+    #[test]
+    fn parses_a_single_quoted_line() {
+        // GIVEN
+        let line = r#"Movie;"Year; released";Director"#;
+
+        // WHEN
+        let got = parse_delimited_line(line, &Delimiter::Byte(b';'), &Dialect::default())
+            .expect("line should've been parsed");
+
+        // THEN
+        assert_eq!(
+            got.iter().collect::<Vec<_>>(),
+            vec!["Movie", "Year; released", "Director"]
+        );
+    }
+
+    #[test]
+    fn unescapes_tabs_and_backslashes() {
+        // GIVEN
+        let input = "a\tb\\c\n".as_bytes();
+        let delimiter = Delimiter::parse(r"\t").expect("delimiter should've been parsed");
+
+        // WHEN
+        let got = get_records_from_delimited_text(
+            input,
+            &delimiter,
+            &Dialect::default(),
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a", "b\\c"]]);
+        assert!(matches!(
+            Delimiter::parse(r"\\").expect("delimiter should've been parsed"),
+            Delimiter::Byte(b'\\')
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_delimiters() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert!(Delimiter::parse("").is_err());
+        assert!(Delimiter::parse("\r\n").is_err());
+        assert!(Delimiter::parse(r"\n").is_err());
+        assert!(Delimiter::parse("\\").is_err());
+        assert!(Delimiter::parse_regex(r"\s*").is_err());
+    }
+
+    #[test]
+    fn detects_tabs() {
+        // GIVEN
+        let input = b"Name\tAge\tCity\nDoe, John\t42\tBerlin\n";
+
+        // WHEN
+        let got = detect_delimiter(input, &Dialect::default());
+
+        // THEN
+        assert_eq!(got, b'\t');
+    }
+
+    #[test]
+    fn ignores_delimiters_inside_quotes() {
+        // GIVEN
+        let input = b"a;b\n\"x;y\";z\n1;2\n";
+
+        // WHEN
+        let got = detect_delimiter(input, &Dialect::default());
+
+        // THEN
+        assert_eq!(got, b';');
+    }
+
+    #[test]
+    fn falls_back_to_comma_if_field_counts_are_inconsistent() {
+        // GIVEN
+        let input = b"a|b\nc\nd:e:f\n";
+
+        // WHEN
+        let got = detect_delimiter(input, &Dialect::default());
+
+        // THEN
+        assert_eq!(got, b',');
+    }
+
+    #[test]
+    fn only_considers_the_first_few_lines() {
+        // GIVEN
+        let mut input = b"a|b\n".repeat(NUM_LINES_TO_SNIFF);
+        input.extend_from_slice(b"c\n");
+
+        // WHEN
+        let got = detect_delimiter(&input, &Dialect::default());
+
+        // THEN
+        assert_eq!(got, b'|');
+    }
+}
@@ -0,0 +1,199 @@
+use crate::aggregate::format_number;
+use crate::values::{CellType, parse_number};
+use csv::StringRecord;
+use std::collections::HashMap;
+
+const DESCRIPTION_HEADER: [&str; 9] = [
+    "Column",
+    "Type",
+    "Non-empty",
+    "Distinct",
+    "Min",
+    "Max",
+    "Mean",
+    "Median",
+    "Most frequent",
+];
+const NUM_MOST_FREQUENT: usize = 3;
+
+/// Profiles every column, producing one row per column (with a header of its own) holding its
+/// inferred type, the number of non-empty and distinct values, the smallest and largest values,
+/// the mean and median (for numeric columns), and the most frequent values. Empty cells are only
+/// accounted for in the first of these.
+pub fn describe(records: &[StringRecord], has_header: bool) -> Vec<StringRecord> {
+    let (header, rows) = match records {
+        [header, rows @ ..] if has_header => (Some(header), rows),
+        rows => (None, rows),
+    };
+    let width = records
+        .iter()
+        .map(StringRecord::len)
+        .max()
+        .unwrap_or_default();
+
+    let mut description = vec![StringRecord::from(DESCRIPTION_HEADER.to_vec())];
+    for index in 0..width {
+        let name = header
+            .and_then(|h| h.get(index))
+            .map_or_else(|| index.to_string(), String::from);
+        let cells = rows
+            .iter()
+            .filter_map(|r| r.get(index))
+            .filter(|c| !c.trim().is_empty())
+            .collect::<Vec<_>>();
+        let cell_type = CellType::infer(cells.iter().copied());
+
+        let mut frequencies: Vec<(&str, usize)> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for cell in &cells {
+            match positions.get(cell) {
+                Some(position) => frequencies[*position].1 += 1,
+                None => {
+                    positions.insert(cell, frequencies.len());
+                    frequencies.push((cell, 1));
+                }
+            }
+        }
+
+        let ordered = |a: &&&str, b: &&&str| cell_type.compare(a, b, false);
+        let min = cells.iter().min_by(ordered).copied().unwrap_or_default();
+        let max = cells.iter().max_by(ordered).copied().unwrap_or_default();
+
+        let (mean, median) = match cell_type {
+            CellType::Number => {
+                let mut numbers = cells
+                    .iter()
+                    .filter_map(|c| parse_number(c))
+                    .collect::<Vec<_>>();
+                numbers.sort_by(f64::total_cmp);
+                (get_mean(&numbers), get_median(&numbers))
+            }
+            _ => (String::new(), String::new()),
+        };
+
+        // a stable sort keeps values that are equally frequent in the order they first appear in
+        frequencies.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let most_frequent = frequencies
+            .iter()
+            .take(NUM_MOST_FREQUENT)
+            .map(|(value, count)| format!("{value} ({count})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        description.push(StringRecord::from(vec![
+            name,
+            cell_type.name().to_string(),
+            cells.len().to_string(),
+            frequencies.len().to_string(),
+            min.to_string(),
+            max.to_string(),
+            mean,
+            median,
+            most_frequent,
+        ]));
+    }
+
+    description
+}
+
+fn get_mean(numbers: &[f64]) -> String {
+    if numbers.is_empty() {
+        return String::new();
+    }
+
+    format_number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+}
+
+/// Expects the numbers to be sorted.
+fn get_median(numbers: &[f64]) -> String {
+    let middle = numbers.len() / 2;
+    let median = match numbers.len() {
+        0 => return String::new(),
+        n if n.is_multiple_of(2) => (numbers[middle - 1] + numbers[middle]) / 2.0,
+        _ => numbers[middle],
+    };
+
+    format_number(median)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    #[test]
+    fn describes_every_column() {
+        // GIVEN
+        let records = vec![
+            StringRecord::from(vec!["Movie", "Year", "Released"]),
+            StringRecord::from(vec!["The Matrix", "1999", "1999-03-31"]),
+            StringRecord::from(vec!["Pulp Fiction", "1994", "1994-10-14"]),
+            StringRecord::from(vec!["Heat", "1995", ""]),
+            StringRecord::from(vec!["Pulp Fiction", "1994", "1994-10-14"]),
+        ];
+
+        // WHEN
+        let got = describe(&records, true);
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                DESCRIPTION_HEADER.to_vec(),
+                vec![
+                    "Movie",
+                    "text",
+                    "4",
+                    "3",
+                    "Heat",
+                    "The Matrix",
+                    "",
+                    "",
+                    "Pulp Fiction (2), The Matrix (1), Heat (1)"
+                ],
+                vec![
+                    "Year",
+                    "number",
+                    "4",
+                    "3",
+                    "1994",
+                    "1999",
+                    "1995.5",
+                    "1994.5",
+                    "1994 (2), 1999 (1), 1995 (1)"
+                ],
+                vec![
+                    "Released",
+                    "date/time",
+                    "3",
+                    "2",
+                    "1994-10-14",
+                    "1999-03-31",
+                    "",
+                    "",
+                    "1994-10-14 (2), 1999-03-31 (1)"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn names_columns_by_index_without_a_header() {
+        // GIVEN
+        let records = vec![
+            StringRecord::from(vec!["a", "1"]),
+            StringRecord::from(vec!["b", "2"]),
+        ];
+
+        // WHEN
+        let got = describe(&records, false);
+
+        // THEN
+        let names = to_rows(got)
+            .into_iter()
+            .skip(1)
+            .map(|r| r[0].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["0", "1"]);
+    }
+}
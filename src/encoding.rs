@@ -0,0 +1,210 @@
+use crate::config::Encoding;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+// characters for bytes 0x80 to 0x9F in windows-1252; the ones it leaves undefined map to the
+// corresponding C1 control characters (as per the WHATWG encoding standard)
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Transcodes the input to UTF-8, dropping any byte order mark. Invalid UTF-8 is left as is
+/// (so that it can be reported row by row) unless `lossy` is set, in which case invalid bytes
+/// are swapped with the replacement character.
+pub fn decode_input(input: Vec<u8>, encoding: Encoding, lossy: bool) -> anyhow::Result<Vec<u8>> {
+    let encoding = match encoding {
+        Encoding::Auto => detect_encoding(&input),
+        e => e,
+    };
+
+    match encoding {
+        Encoding::Auto | Encoding::Utf8 => {
+            let input = match input.strip_prefix(UTF8_BOM) {
+                Some(rest) => rest.to_vec(),
+                None => input,
+            };
+
+            if lossy {
+                Ok(String::from_utf8_lossy(&input).into_owned().into_bytes())
+            } else {
+                Ok(input)
+            }
+        }
+        Encoding::Utf16le => decode_utf16(&input, UTF16LE_BOM, u16::from_le_bytes, lossy),
+        Encoding::Utf16be => decode_utf16(&input, UTF16BE_BOM, u16::from_be_bytes, lossy),
+        Encoding::Latin1 => Ok(input
+            .iter()
+            .map(|b| char::from(*b))
+            .collect::<String>()
+            .into_bytes()),
+        Encoding::Windows1252 => Ok(input
+            .iter()
+            .map(|b| match b {
+                0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(b - 0x80)],
+                _ => char::from(*b),
+            })
+            .collect::<String>()
+            .into_bytes()),
+    }
+}
+
+fn detect_encoding(input: &[u8]) -> Encoding {
+    if input.starts_with(UTF16LE_BOM) {
+        Encoding::Utf16le
+    } else if input.starts_with(UTF16BE_BOM) {
+        Encoding::Utf16be
+    } else {
+        Encoding::Utf8
+    }
+}
+
+fn decode_utf16(
+    input: &[u8],
+    bom: &[u8],
+    to_unit: fn([u8; 2]) -> u16,
+    lossy: bool,
+) -> anyhow::Result<Vec<u8>> {
+    let payload = input.strip_prefix(bom).unwrap_or(input);
+    let bom_len = input.len() - payload.len();
+    let input = payload;
+    if !input.len().is_multiple_of(2) && !lossy {
+        anyhow::bail!("input is not valid UTF-16: it has an odd number of bytes");
+    }
+
+    let units = input
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+
+    let mut decoded = String::with_capacity(input.len());
+    // the number of code units decoded so far, which points to the offending one on errors
+    let mut num_units = 0;
+    for result in char::decode_utf16(units) {
+        match result {
+            Ok(c) => {
+                decoded.push(c);
+                num_units += c.len_utf16();
+            }
+            Err(_) if lossy => {
+                decoded.push(char::REPLACEMENT_CHARACTER);
+                num_units += 1;
+            }
+            Err(e) => anyhow::bail!(
+                "input is not valid UTF-16: found unpaired surrogate {:#06x} at byte {}",
+                e.unpaired_surrogate(),
+                bom_len + num_units * 2
+            ),
+        }
+    }
+
+    if !input.len().is_multiple_of(2) {
+        decoded.push(char::REPLACEMENT_CHARACTER);
+    }
+
+    Ok(decoded.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_utf8_bom() {
+        // GIVEN
+        let input = b"\xEF\xBB\xBFMovie,Year".to_vec();
+
+        // WHEN
+        let got = decode_input(input, Encoding::Auto, false).expect("input should've been decoded");
+
+        // THEN
+        assert_eq!(got, b"Movie,Year");
+    }
+
+    #[test]
+    fn detects_utf16_from_bom() {
+        // GIVEN
+        let input = b"\xFF\xFEA\x00,\x00\xE9\x00".to_vec();
+
+        // WHEN
+        let got = decode_input(input, Encoding::Auto, false).expect("input should've been decoded");
+
+        // THEN
+        assert_eq!(
+            String::from_utf8(got).expect("output should be UTF-8"),
+            "A,é"
+        );
+    }
+
+    #[test]
+    fn decodes_utf16be() {
+        // GIVEN
+        let input = b"\x00A\xD8\x3D\xDE\x00".to_vec();
+
+        // WHEN
+        let got =
+            decode_input(input, Encoding::Utf16be, false).expect("input should've been decoded");
+
+        // THEN
+        assert_eq!(
+            String::from_utf8(got).expect("output should be UTF-8"),
+            "A😀"
+        );
+    }
+
+    #[test]
+    fn fails_for_unpaired_surrogates_unless_lossy() {
+        // GIVEN
+        let input = b"A\x00\x3D\xD8B\x00".to_vec();
+
+        // WHEN
+        let got_strict = decode_input(input.clone(), Encoding::Utf16le, false);
+        let got_lossy =
+            decode_input(input, Encoding::Utf16le, true).expect("input should've been decoded");
+
+        // THEN
+        assert!(got_strict.is_err());
+        assert_eq!(
+            String::from_utf8(got_lossy).expect("output should be UTF-8"),
+            "A\u{FFFD}B"
+        );
+
+        // the offset accounts for the BOM, and surrogate pairs preceding the unpaired surrogate
+        let input = b"\xFF\xFE\x3D\xD8\x00\xDEA\x00\x00\xD8A\x00".to_vec();
+        let got = decode_input(input, Encoding::Auto, false);
+        assert_eq!(
+            got.expect_err("input shouldn't have been decoded")
+                .to_string(),
+            "input is not valid UTF-16: found unpaired surrogate 0xd800 at byte 8"
+        );
+    }
+
+    #[test]
+    fn decodes_windows_1252() {
+        // GIVEN
+        let input = b"caf\xE9 \x80 \x93quoted\x94".to_vec();
+
+        // WHEN
+        let got = decode_input(input, Encoding::Windows1252, false)
+            .expect("input should've been decoded");
+
+        // THEN
+        assert_eq!(
+            String::from_utf8(got).expect("output should be UTF-8"),
+            "café € “quoted”"
+        );
+    }
+
+    #[test]
+    fn replaces_invalid_utf8_when_lossy() {
+        // GIVEN
+        let input = b"Am\xE9lie".to_vec();
+
+        // WHEN
+        let got = decode_input(input, Encoding::Utf8, true).expect("input should've been decoded");
+
+        // THEN
+        assert_eq!(got, "Am\u{FFFD}lie".as_bytes());
+    }
+}
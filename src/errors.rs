@@ -0,0 +1,230 @@
+use crate::config::RowErrorPolicy;
+use std::fmt;
+
+const MAX_SNIPPET_LEN: usize = 80;
+const MAX_LINES_IN_SUMMARY: usize = 20;
+
+/// An error in the input data, along with where in the input it occurred.
+#[derive(Debug)]
+pub struct InputError {
+    pub line: u64,
+    pub byte: Option<u64>,
+    pub column: Option<u64>,
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(line: u64, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            byte: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_byte(mut self, byte: u64) -> Self {
+        self.byte = Some(byte);
+        self
+    }
+
+    pub fn with_column(mut self, column: u64) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    fn location(&self) -> String {
+        let mut location = format!("line {}", self.line);
+        if let Some(column) = self.column {
+            location.push_str(&format!(", column {column}"));
+        }
+        if let Some(byte) = self.byte {
+            location.push_str(&format!(", byte {byte}"));
+        }
+
+        location
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location(), self.message)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Keeps track of rows that were skipped because they couldn't be parsed.
+pub struct RowErrors {
+    policy: RowErrorPolicy,
+    skipped: Vec<InputError>,
+}
+
+impl RowErrors {
+    pub fn new(policy: RowErrorPolicy) -> Self {
+        Self {
+            policy,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Returns the error back if rows with errors aren't to be skipped, or if the error doesn't
+    /// point to a row in the input.
+    pub fn handle(&mut self, error: anyhow::Error) -> anyhow::Result<()> {
+        if self.policy == RowErrorPolicy::Fail {
+            return Err(error);
+        }
+
+        self.skipped.push(error.downcast::<InputError>()?);
+        Ok(())
+    }
+
+    pub fn skipped_lines(&self) -> Vec<u64> {
+        self.skipped.iter().map(|e| e.line).collect()
+    }
+
+    /// A summary of the skipped rows, if they are to be reported; the source is only mentioned
+    /// if provided.
+    pub fn summary(&self, source: Option<&str>) -> Option<String> {
+        if self.policy != RowErrorPolicy::Warn || self.skipped.is_empty() {
+            return None;
+        }
+
+        let mut lines = self
+            .skipped_lines()
+            .iter()
+            .take(MAX_LINES_IN_SUMMARY)
+            .map(u64::to_string)
+            .collect::<Vec<_>>();
+        if self.skipped.len() > MAX_LINES_IN_SUMMARY {
+            lines.push("…".to_string());
+        }
+
+        let source = source.map(|s| format!(" in {s}")).unwrap_or_default();
+
+        Some(format!(
+            "warning: skipped {} row(s){source} that couldn't be parsed (lines: {})",
+            self.skipped.len(),
+            lines.join(", ")
+        ))
+    }
+}
+
+/// Converts errors encountered while reading the input line by line, pointing invalid UTF-8 to
+/// the line it was found on.
+pub fn get_line_read_error(error: std::io::Error, line: u64) -> anyhow::Error {
+    match error.kind() {
+        std::io::ErrorKind::InvalidData => InputError::new(line, "line is not valid UTF-8").into(),
+        _ => anyhow::Error::new(error).context(format!("couldn't read line {line}")),
+    }
+}
+
+/// Adds the name of the source, and a snippet of the offending line to errors that point to a
+/// location in the input; other errors are returned as is.
+pub fn locate_error(error: anyhow::Error, source: &str, input: &[u8]) -> anyhow::Error {
+    let Some(input_error) = error.downcast_ref::<InputError>() else {
+        return error;
+    };
+
+    let mut message = format!(
+        "couldn't parse {source} at {}: {}",
+        input_error.location(),
+        input_error.message
+    );
+    if let Some(snippet) = get_snippet(input, input_error.line) {
+        message.push_str(&format!("\n\n  {} | {snippet}", input_error.line));
+    }
+
+    anyhow::anyhow!(message)
+}
+
+fn get_snippet(input: &[u8], line: u64) -> Option<String> {
+    let line = input
+        .split(|b| *b == b'\n')
+        .nth(usize::try_from(line).ok()?.checked_sub(1)?)?;
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end_matches('\r');
+
+    if line.chars().count() > MAX_SNIPPET_LEN {
+        Some(format!(
+            "{}…",
+            line.chars().take(MAX_SNIPPET_LEN).collect::<String>()
+        ))
+    } else {
+        Some(line.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_source_and_snippet_to_input_errors() {
+        // GIVEN
+        let input = b"a,b\nc\nd,e\n";
+        let error = anyhow::Error::new(InputError::new(2, "something's off").with_byte(4));
+
+        // WHEN
+        let got = locate_error(error, "data.csv", input);
+
+        // THEN
+        assert_eq!(
+            got.to_string(),
+            "couldn't parse data.csv at line 2, byte 4: something's off\n\n  2 | c"
+        );
+    }
+
+    #[test]
+    fn truncates_long_snippets() {
+        // GIVEN
+        let input = "x".repeat(100);
+        let error = anyhow::Error::new(InputError::new(1, "something's off").with_column(3));
+
+        // WHEN
+        let got = locate_error(error, "stdin", input.as_bytes());
+
+        // THEN
+        assert_eq!(
+            got.to_string(),
+            format!(
+                "couldn't parse stdin at line 1, column 3: something's off\n\n  1 | {}…",
+                "x".repeat(MAX_SNIPPET_LEN)
+            )
+        );
+    }
+
+    #[test]
+    fn summarizes_skipped_rows() {
+        // GIVEN
+        let mut row_errors = RowErrors::new(RowErrorPolicy::Warn);
+        for line in 1..=(MAX_LINES_IN_SUMMARY as u64 + 1) {
+            row_errors
+                .handle(InputError::new(line, "something's off").into())
+                .expect("error should've been skipped");
+        }
+
+        // WHEN
+        let got = row_errors.summary(None);
+
+        // THEN
+        assert_eq!(
+            got.as_deref(),
+            Some(
+                "warning: skipped 21 row(s) that couldn't be parsed (lines: 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, …)"
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_other_errors_untouched() {
+        // GIVEN
+        let error = anyhow::anyhow!("something else");
+
+        // WHEN
+        let got = locate_error(error, "stdin", b"a,b\n");
+
+        // THEN
+        assert_eq!(got.to_string(), "something else");
+    }
+}
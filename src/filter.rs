@@ -0,0 +1,463 @@
+use crate::values::{CellType, parse_number};
+use csv::StringRecord;
+use regex::Regex;
+use std::cmp::Ordering;
+
+/// A predicate over rows, parsed from expressions like `Year >= 1995 && Genre ~ "Drama"`.
+///
+/// Columns are referenced by their header name (wrapped in backticks if it contains anything
+/// other than letters, digits, underscores, and dots), or by their index (eg. `$0`). Cells can be
+/// compared with strings, numbers, and `null` (which matches empty cells) using `==`, `!=`, `<`,
+/// `<=`, `>`, and `>=`, or matched against a regex using `~` and `!~`. Comparisons can be combined
+/// with `&&`, `||`, `!`, and parentheses.
+#[derive(Debug)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(input: &str, header: Option<&StringRecord>) -> anyhow::Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            input_len: input.len(),
+            header,
+        };
+
+        let expr = parser.parse_or()?;
+        if let Some((_, position)) = parser.tokens.get(parser.position) {
+            anyhow::bail!("unexpected token at position {position}");
+        }
+
+        Ok(Self { expr })
+    }
+
+    pub fn matches(&self, record: &StringRecord) -> bool {
+        self.expr.evaluate(record)
+    }
+}
+
+/// Drops rows (other than the header, if any) that the filter doesn't match.
+pub fn filter_rows(records: &mut Vec<StringRecord>, filter: &Filter, has_header: bool) {
+    let mut i = 0;
+    records.retain(|record| {
+        let keep = (has_header && i == 0) || filter.matches(record);
+        i += 1;
+        keep
+    });
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Operator, Operand),
+    Match(Operand, Regex, bool),
+}
+
+#[derive(Debug)]
+enum Operand {
+    Column(usize),
+    Text(String),
+    Number(f64),
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Expr {
+    fn evaluate(&self, record: &StringRecord) -> bool {
+        match self {
+            Self::And(left, right) => left.evaluate(record) && right.evaluate(record),
+            Self::Or(left, right) => left.evaluate(record) || right.evaluate(record),
+            Self::Not(expr) => !expr.evaluate(record),
+            Self::Match(operand, regex, negated) => match operand.value(record) {
+                Some(value) => regex.is_match(&value) != *negated,
+                None => *negated,
+            },
+            Self::Compare(left, operator, right) => compare(left, *operator, right, record),
+        }
+    }
+}
+
+impl Operand {
+    /// The textual value of the operand for a row; empty and missing cells are considered null.
+    fn value(&self, record: &StringRecord) -> Option<String> {
+        match self {
+            Self::Column(index) => record
+                .get(*index)
+                .filter(|c| !c.trim().is_empty())
+                .map(String::from),
+            Self::Text(text) => Some(text.clone()),
+            Self::Number(number) => Some(number.to_string()),
+            Self::Null => None,
+        }
+    }
+}
+
+fn compare(left: &Operand, operator: Operator, right: &Operand, record: &StringRecord) -> bool {
+    let (left_value, right_value) = match (left.value(record), right.value(record)) {
+        (Some(l), Some(r)) => (l, r),
+        (l, r) => {
+            // only equality checks make sense for nulls
+            return match operator {
+                Operator::Eq => l.is_none() && r.is_none(),
+                Operator::Ne => l.is_some() || r.is_some(),
+                _ => false,
+            };
+        }
+    };
+
+    let ordering = if matches!(left, Operand::Number(_)) || matches!(right, Operand::Number(_)) {
+        // a number on either side calls for a numeric comparison
+        match (parse_number(&left_value), parse_number(&right_value)) {
+            (Some(l), Some(r)) => l.total_cmp(&r),
+            _ => return operator == Operator::Ne,
+        }
+    } else {
+        let cell_type = CellType::infer([left_value.as_str(), right_value.as_str()]);
+        cell_type.compare(&left_value, &right_value, false)
+    };
+
+    match operator {
+        Operator::Eq => ordering == Ordering::Equal,
+        Operator::Ne => ordering != Ordering::Equal,
+        Operator::Lt => ordering == Ordering::Less,
+        Operator::Le => ordering != Ordering::Greater,
+        Operator::Gt => ordering == Ordering::Greater,
+        Operator::Ge => ordering != Ordering::Less,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    Operator(Operator),
+    Match,
+    NotMatch,
+    Text(String),
+    Number(f64),
+    Name(String),
+    Index(usize),
+    Null,
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<(Token, usize)>> {
+    let chars = input.char_indices().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(&(position, c)) = chars.get(i) {
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Operator(Operator::Eq), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::Ne), 2),
+            ('!', Some('~')) => (Token::NotMatch, 2),
+            ('!', _) => (Token::Not, 1),
+            ('<', Some('=')) => (Token::Operator(Operator::Le), 2),
+            ('<', _) => (Token::Operator(Operator::Lt), 1),
+            ('>', Some('=')) => (Token::Operator(Operator::Ge), 2),
+            ('>', _) => (Token::Operator(Operator::Gt), 1),
+            ('~', _) => (Token::Match, 1),
+            ('"' | '\'' | '`', _) => {
+                let mut value = String::new();
+                let mut len = 1;
+                let mut closed = false;
+                while let Some(&(_, ch)) = chars.get(i + len) {
+                    len += 1;
+                    match ch {
+                        '\\' if c != '`' => {
+                            let Some(&(_, escaped)) = chars.get(i + len) else {
+                                break;
+                            };
+                            // only quotes and backslashes need escaping; anything else is kept
+                            // as is, so that regexes like "\d+" work as expected
+                            if escaped != c && escaped != '\\' {
+                                value.push('\\');
+                            }
+                            value.push(escaped);
+                            len += 1;
+                        }
+                        ch if ch == c => {
+                            closed = true;
+                            break;
+                        }
+                        ch => value.push(ch),
+                    }
+                }
+                if !closed {
+                    anyhow::bail!("unterminated quote at position {position}");
+                }
+
+                if c == '`' {
+                    (Token::Name(value), len)
+                } else {
+                    (Token::Text(value), len)
+                }
+            }
+            ('$', _) => {
+                let digits = take_while(&chars[i + 1..], |c| c.is_ascii_digit());
+                let index = digits.parse::<usize>().map_err(|_| {
+                    anyhow::anyhow!("expected a column index at position {position}")
+                })?;
+                (Token::Index(index), digits.chars().count() + 1)
+            }
+            (c, next)
+                if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let literal = take_while(&chars[i..], |c| {
+                    c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+                });
+                let number = parse_number(&literal)
+                    .ok_or_else(|| anyhow::anyhow!("invalid number at position {position}"))?;
+                (Token::Number(number), literal.chars().count())
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let name = take_while(&chars[i..], |c| {
+                    c.is_alphanumeric() || matches!(c, '_' | '.')
+                });
+                let len = name.chars().count();
+                if name == "null" {
+                    (Token::Null, len)
+                } else {
+                    (Token::Name(name), len)
+                }
+            }
+            (c, _) => anyhow::bail!("unexpected character '{c}' at position {position}"),
+        };
+
+        tokens.push((token, position));
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+fn take_while(chars: &[(usize, char)], predicate: impl Fn(char) -> bool) -> String {
+    chars
+        .iter()
+        .map(|(_, c)| *c)
+        .take_while(|c| predicate(*c))
+        .collect()
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    input_len: usize,
+    header: Option<&'a StringRecord>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> anyhow::Result<(Token, usize)> {
+        let token = self.tokens.get(self.position).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "unexpected end of expression at position {}",
+                self.input_len
+            )
+        })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> anyhow::Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.position += 1;
+                Ok(Expr::Not(Box::new(self.parse_not()?)))
+            }
+            Some(Token::LeftParen) => {
+                self.position += 1;
+                let expr = self.parse_or()?;
+                match self.next()? {
+                    (Token::RightParen, _) => Ok(expr),
+                    (_, position) => anyhow::bail!("expected ')' at position {position}"),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> anyhow::Result<Expr> {
+        let left = self.parse_operand()?;
+        match self.next()? {
+            (Token::Operator(operator), _) => {
+                let right = self.parse_operand()?;
+                Ok(Expr::Compare(left, operator, right))
+            }
+            (token @ (Token::Match | Token::NotMatch), _) => match self.next()? {
+                (Token::Text(pattern), position) => {
+                    let regex = Regex::new(&pattern).map_err(|e| {
+                        anyhow::anyhow!("invalid regex at position {position}: {e}")
+                    })?;
+                    Ok(Expr::Match(left, regex, token == Token::NotMatch))
+                }
+                (_, position) => anyhow::bail!("expected a quoted regex at position {position}"),
+            },
+            (_, position) => anyhow::bail!("expected a comparison operator at position {position}"),
+        }
+    }
+
+    fn parse_operand(&mut self) -> anyhow::Result<Operand> {
+        match self.next()? {
+            (Token::Text(text), _) => Ok(Operand::Text(text)),
+            (Token::Number(number), _) => Ok(Operand::Number(number)),
+            (Token::Null, _) => Ok(Operand::Null),
+            (Token::Index(index), _) => Ok(Operand::Column(index)),
+            (Token::Name(name), position) => {
+                let Some(header) = self.header else {
+                    anyhow::bail!(
+                        "columns can only be referred to by name if there's a header (at position {position})"
+                    );
+                };
+                header
+                    .iter()
+                    .position(|h| h == name)
+                    .map(Operand::Column)
+                    .ok_or_else(|| anyhow::anyhow!("there's no column named \"{name}\""))
+            }
+            (_, position) => {
+                anyhow::bail!("expected a column, string, number, or null at position {position}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_data() -> Vec<StringRecord> {
+        vec![
+            StringRecord::from(vec!["Movie", "Year", "Genre", "Rating"]),
+            StringRecord::from(vec!["The Matrix", "1999", "Science Fiction", "8.7"]),
+            StringRecord::from(vec!["Pulp Fiction", "1994", "Crime", ""]),
+            StringRecord::from(vec!["The Shawshank Redemption", "1994", "Drama", "9.3"]),
+            StringRecord::from(vec!["Million Dollar Baby", "2004", "Sports Drama", "8.1"]),
+        ]
+    }
+
+    fn get_movies(expression: &str) -> Vec<String> {
+        let mut data = generate_data();
+        let filter = Filter::parse(expression, data.first()).expect("filter should've been parsed");
+        filter_rows(&mut data, &filter, true);
+
+        data.iter()
+            .skip(1)
+            .filter_map(|r| r.get(0))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn filters_on_numbers_and_regexes() {
+        // GIVEN
+        // WHEN
+        let got = get_movies(r#"Year >= 1995 && Genre ~ "Drama""#);
+
+        // THEN
+        assert_eq!(got, vec!["Million Dollar Baby"]);
+    }
+
+    #[test]
+    fn supports_grouping_negation_and_nulls() {
+        // GIVEN
+        // WHEN
+        let got = get_movies(r#"!(Rating == null || $1 < 1995) && Movie !~ '^The'"#);
+
+        // THEN
+        assert_eq!(got, vec!["Million Dollar Baby"]);
+    }
+
+    #[test]
+    fn compares_strings() {
+        // GIVEN
+        // WHEN
+        let got = get_movies(r#"Genre == "Crime" || `Movie` > "T""#);
+
+        // THEN
+        assert_eq!(
+            got,
+            vec!["The Matrix", "Pulp Fiction", "The Shawshank Redemption"]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_expressions() {
+        // GIVEN
+        let data = generate_data();
+
+        // WHEN
+        let errors = [
+            "Year >",
+            "Year 1995",
+            "Director == 'x'",
+            "(Year > 1 ",
+            "Genre ~ Drama",
+        ]
+        .into_iter()
+        .map(|e| {
+            Filter::parse(e, data.first())
+                .expect_err("expression shouldn't have been parsed")
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+
+        // THEN
+        assert_eq!(
+            errors,
+            vec![
+                "unexpected end of expression at position 6",
+                "expected a comparison operator at position 5",
+                r#"there's no column named "Director""#,
+                "unexpected end of expression at position 10",
+                "expected a quoted regex at position 8",
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::aggregate::Function;
+use crate::types::{ColSelector, parse_col_selector};
+use crate::values::CellType;
+use csv::StringRecord;
+
+/// A function to compute in the footer, either for a specific column or for every column, as
+/// passed to --footer (eg. `sum`, `Year:avg`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FooterSpec {
+    pub col: Option<ColSelector>,
+    pub function: Function,
+}
+
+pub fn parse_footer_spec(value: &str) -> Result<FooterSpec, String> {
+    match value.rsplit_once(':') {
+        Some((col, function)) => Ok(FooterSpec {
+            col: Some(parse_col_selector(col)?),
+            function: Function::parse(function)?,
+        }),
+        None => Ok(FooterSpec {
+            col: None,
+            function: Function::parse(value)?,
+        }),
+    }
+}
+
+/// Computes one footer row per function (in the order they're first specified in) over every row
+/// but the header, if any. Functions not tied to a column are computed for every numeric column.
+/// Every value is prefixed with the name of its function (eg. `sum: 42`), so that rows can be told
+/// apart regardless of which columns end up being displayed.
+pub fn get_footer(
+    records: &[StringRecord],
+    specs: &[FooterSpec],
+    has_header: bool,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let (header, rows) = match records {
+        [header, rows @ ..] if has_header => (Some(header), rows),
+        rows => (None, rows),
+    };
+    let width = records
+        .iter()
+        .map(StringRecord::len)
+        .max()
+        .unwrap_or_default();
+
+    let mut functions: Vec<Function> = Vec::new();
+    for spec in specs {
+        if !functions.contains(&spec.function) {
+            functions.push(spec.function);
+        }
+    }
+
+    let mut footer = Vec::new();
+    for function in functions {
+        let mut cells = vec![String::new(); width];
+        for spec in specs.iter().filter(|s| s.function == function) {
+            let indices = match &spec.col {
+                Some(col) => col.resolve(header, width)?,
+                None => (0..width).collect(),
+            };
+
+            for index in indices {
+                let column = rows.iter().filter_map(|r| r.get(index)).collect::<Vec<_>>();
+                if spec.col.is_none() && CellType::infer(column.iter().copied()) != CellType::Number
+                {
+                    continue;
+                }
+
+                let label = match header.and_then(|h| h.get(index)) {
+                    Some(name) => format!("{}({name})", function.name()),
+                    None => format!("{}(${index})", function.name()),
+                };
+                let value = function.compute(&column, &label)?;
+                cells[index] = format!("{}: {value}", function.name());
+            }
+        }
+
+        footer.push(StringRecord::from(cells));
+    }
+
+    Ok(footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    fn generate_data() -> Vec<StringRecord> {
+        vec![
+            StringRecord::from(vec!["Item", "Quantity", "Cost"]),
+            StringRecord::from(vec!["Compute", "3", "120.5"]),
+            StringRecord::from(vec!["Storage", "", "30.25"]),
+            StringRecord::from(vec!["Network", "2", "9"]),
+        ]
+    }
+
+    #[test]
+    fn computes_functions_for_every_applicable_column() {
+        // GIVEN
+        let specs = ["sum", "count"]
+            .into_iter()
+            .map(|s| parse_footer_spec(s).expect("spec should've been parsed"))
+            .collect::<Vec<_>>();
+
+        // WHEN
+        let got =
+            get_footer(&generate_data(), &specs, true).expect("footer should've been computed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["", "sum: 5", "sum: 159.75"],
+                vec!["", "count: 2", "count: 3"]
+            ]
+        );
+    }
+
+    #[test]
+    fn computes_functions_for_specific_columns() {
+        // GIVEN
+        let specs = ["Quantity:avg", "-1:sum", "Cost:avg"]
+            .into_iter()
+            .map(|s| parse_footer_spec(s).expect("spec should've been parsed"))
+            .collect::<Vec<_>>();
+
+        // WHEN
+        let got =
+            get_footer(&generate_data(), &specs, true).expect("footer should've been computed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["", "avg: 2.5", "avg: 53.25"],
+                vec!["", "", "sum: 159.75"]
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_to_sum_text_column() {
+        // GIVEN
+        let specs = vec![parse_footer_spec("Item:sum").expect("spec should've been parsed")];
+
+        // WHEN
+        let got = get_footer(&generate_data(), &specs, true);
+
+        // THEN
+        assert_eq!(
+            got.expect_err("footer shouldn't have been computed")
+                .to_string(),
+            r#"couldn't compute sum(Item): "Compute" is not a number"#
+        );
+    }
+}
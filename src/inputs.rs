@@ -0,0 +1,261 @@
+use anyhow::Context;
+use csv::StringRecord;
+use std::collections::HashMap;
+use std::path::Path;
+
+const GLOB_CHARS: [char; 3] = ['*', '?', '['];
+
+/// Records read from one of the inputs.
+pub struct Input {
+    pub source: String,
+    pub records: Vec<StringRecord>,
+}
+
+/// Expands glob patterns among the input paths into the files they match (in alphabetical order).
+/// Paths that exist as is, or that don't contain any glob characters, are left untouched.
+pub fn expand_input_paths(paths: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if !path.contains(GLOB_CHARS) || Path::new(path).exists() {
+            expanded.push(path.clone());
+            continue;
+        }
+
+        let matches = glob::glob(path)
+            .with_context(|| format!("invalid glob pattern: {path}"))?
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("couldn't expand glob pattern: {path}"))?;
+        let files = matches
+            .into_iter()
+            .filter(|p| p.is_file())
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+
+        if files.is_empty() {
+            anyhow::bail!("no files match the pattern {path}");
+        }
+
+        expanded.extend(files);
+    }
+
+    Ok(expanded)
+}
+
+/// Concatenates the records from all inputs into one table.
+///
+/// If `dedupe_headers` is set, every input is expected to start with the same header line, which
+/// is only kept for the first one. If `align_columns` is set (and the inputs have a header), cells
+/// are instead lined up by their column's name, with columns appearing in the order they're first
+/// seen in. If a source column is requested, it's added as the first column, holding the source
+/// each row came from (and the column's name for the header, if the inputs have one).
+pub fn merge_inputs(
+    inputs: Vec<Input>,
+    has_header: bool,
+    dedupe_headers: bool,
+    align_columns: bool,
+    source_column: Option<&str>,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let (inputs, dedupe_headers) = if align_columns && has_header {
+        (align_columns_by_name(inputs), true)
+    } else {
+        (inputs, dedupe_headers)
+    };
+
+    let header = inputs.first().and_then(|i| i.records.first()).cloned();
+    if dedupe_headers {
+        for input in inputs.iter().skip(1) {
+            match input.records.first() {
+                Some(first) if Some(first) != header.as_ref() => anyhow::bail!(
+                    "can't de-duplicate headers: the first line of {} doesn't match that of {}",
+                    input.source,
+                    inputs[0].source
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut merged = Vec::new();
+    for (i, input) in inputs.into_iter().enumerate() {
+        let to_skip = usize::from(dedupe_headers && i > 0);
+        for (j, record) in input.records.into_iter().enumerate().skip(to_skip) {
+            let Some(column) = source_column else {
+                merged.push(record);
+                continue;
+            };
+
+            let source = if has_header && i == 0 && j == 0 {
+                column
+            } else {
+                &input.source
+            };
+            let mut with_source = StringRecord::from(vec![source]);
+            with_source.extend(record.iter());
+            merged.push(with_source);
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Lays out the records of every input as per the union of their headers, so that each of them
+/// starts with that same header.
+fn align_columns_by_name(inputs: Vec<Input>) -> Vec<Input> {
+    let mut header: Vec<String> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut layouts = Vec::new();
+    for input in &inputs {
+        let layout = input
+            .records
+            .first()
+            .map(|h| {
+                h.iter()
+                    .map(|name| {
+                        *positions.entry(name.to_string()).or_insert_with(|| {
+                            header.push(name.to_string());
+                            header.len() - 1
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        layouts.push(layout);
+    }
+
+    let header = StringRecord::from(header);
+    inputs
+        .into_iter()
+        .zip(layouts)
+        .map(|(input, layout)| {
+            let mut records = vec![header.clone()];
+            for record in input.records.iter().skip(1) {
+                let mut cells = vec![""; header.len()];
+                for (cell, position) in record.iter().zip(layout.iter()) {
+                    cells[*position] = cell;
+                }
+                records.push(StringRecord::from(cells));
+            }
+
+            Input {
+                source: input.source,
+                records,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    fn generate_inputs(second_header: &str) -> Vec<Input> {
+        vec![
+            Input {
+                source: "a.csv".to_string(),
+                records: vec![
+                    StringRecord::from(vec!["Movie", "Year"]),
+                    StringRecord::from(vec!["The Matrix", "1999"]),
+                ],
+            },
+            Input {
+                source: "b.csv".to_string(),
+                records: vec![
+                    StringRecord::from(vec![second_header, "Year"]),
+                    StringRecord::from(vec!["Pulp Fiction", "1994"]),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn adds_source_column_and_drops_repeated_headers() {
+        // GIVEN
+        let inputs = generate_inputs("Movie");
+
+        // WHEN
+        let got =
+            merge_inputs(inputs, true, true, false, Some("File")).expect("inputs should've merged");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["File", "Movie", "Year"],
+                vec!["a.csv", "The Matrix", "1999"],
+                vec!["b.csv", "Pulp Fiction", "1994"],
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_every_line_without_deduplication() {
+        // GIVEN
+        let inputs = generate_inputs("Movie");
+
+        // WHEN
+        let got = merge_inputs(inputs, true, false, false, None).expect("inputs should've merged");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["Movie", "Year"],
+                vec!["The Matrix", "1999"],
+                vec!["Movie", "Year"],
+                vec!["Pulp Fiction", "1994"],
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_to_dedupe_differing_headers() {
+        // GIVEN
+        let inputs = generate_inputs("Film");
+
+        // WHEN
+        let got = merge_inputs(inputs, true, true, false, None);
+
+        // THEN
+        assert_eq!(
+            got.expect_err("headers shouldn't have been de-duplicated")
+                .to_string(),
+            "can't de-duplicate headers: the first line of b.csv doesn't match that of a.csv"
+        );
+    }
+
+    #[test]
+    fn aligns_columns_by_name() {
+        // GIVEN
+        let inputs = vec![
+            Input {
+                source: "a.ndjson".to_string(),
+                records: vec![
+                    StringRecord::from(vec!["a", "b"]),
+                    StringRecord::from(vec!["1", "2"]),
+                ],
+            },
+            Input {
+                source: "b.ndjson".to_string(),
+                records: vec![
+                    StringRecord::from(vec!["b", "c"]),
+                    StringRecord::from(vec!["3", "4"]),
+                ],
+            },
+        ];
+
+        // WHEN
+        let got =
+            merge_inputs(inputs, true, false, true, Some("File")).expect("inputs should've merged");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["File", "a", "b", "c"],
+                vec!["a.ndjson", "1", "2", ""],
+                vec!["b.ndjson", "", "3", "4"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use crate::config::{ArrayMode, FlattenConfig};
+use crate::errors::{InputError, RowErrors, get_line_read_error};
+use csv::StringRecord;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+
+pub fn get_records_from_json<R: Read>(
+    reader: R,
+    config: &FlattenConfig,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let value: Value = serde_json::from_reader(reader).map_err(|e| get_json_error(e, 0))?;
+
+    let items = match value {
+        Value::Array(items) => items,
+        _ => anyhow::bail!("JSON input needs to be an array of objects or an array of arrays"),
+    };
+
+    if items.iter().all(Value::is_object) {
+        let mut table = ObjectTable::default();
+        for object in items.iter().filter_map(Value::as_object) {
+            table.push(object, config);
+        }
+
+        return Ok(table.into_records());
+    }
+
+    if items.iter().all(Value::is_array) {
+        return Ok(items
+            .iter()
+            .filter_map(Value::as_array)
+            .map(|row| {
+                StringRecord::from(
+                    row.iter()
+                        .map(|value| get_cell(value, config.array_mode))
+                        .collect::<Vec<String>>(),
+                )
+            })
+            .collect());
+    }
+
+    anyhow::bail!("JSON input needs to be an array of objects or an array of arrays")
+}
+
+pub fn get_records_from_ndjson<R: BufRead>(
+    reader: R,
+    config: &FlattenConfig,
+    row_errors: &mut RowErrors,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let mut table = ObjectTable::default();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_num = i as u64 + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                row_errors.handle(get_line_read_error(e, line_num))?;
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(Value::Object(object)) => table.push(&object, config),
+            Ok(_) => {
+                row_errors.handle(InputError::new(line_num, "expected a JSON object").into())?
+            }
+            Err(e) => row_errors.handle(get_json_error(e, line_num - 1))?,
+        }
+    }
+
+    Ok(table.into_records())
+}
+
+/// Points JSON syntax errors to where they occurred; `line_offset` is added to the line number
+/// reported by the parser.
+fn get_json_error(error: serde_json::Error, line_offset: u64) -> anyhow::Error {
+    if error.is_io() {
+        return error.into();
+    }
+
+    // the error's message ends with its position, which is reported separately
+    let message = error.to_string();
+    let suffix = format!(" at line {} column {}", error.line(), error.column());
+    let message = message.strip_suffix(&suffix).unwrap_or(&message);
+
+    InputError::new(error.line() as u64 + line_offset, message)
+        .with_column(error.column() as u64)
+        .into()
+}
+
+/// Collects rows from JSON objects, adding a column whenever a new (flattened) key shows up.
+#[derive(Default)]
+struct ObjectTable {
+    headers: Vec<String>,
+    positions: HashMap<String, usize>,
+    rows: Vec<Vec<String>>,
+}
+
+impl ObjectTable {
+    fn push(&mut self, object: &Map<String, Value>, config: &FlattenConfig) {
+        let mut cells = Vec::new();
+        for (key, value) in object {
+            flatten(
+                key.clone(),
+                value,
+                config.depth,
+                config.array_mode,
+                &mut cells,
+            );
+        }
+
+        let mut row = vec![String::new(); self.headers.len()];
+        for (key, cell) in cells {
+            let position = match self.positions.get(&key) {
+                Some(position) => *position,
+                None => {
+                    self.positions.insert(key.clone(), self.headers.len());
+                    self.headers.push(key);
+                    row.push(String::new());
+                    self.headers.len() - 1
+                }
+            };
+            row[position] = cell;
+        }
+
+        self.rows.push(row);
+    }
+
+    fn into_records(self) -> Vec<StringRecord> {
+        if self.headers.is_empty() {
+            return Vec::new();
+        }
+
+        let num_cols = self.headers.len();
+        let mut records = vec![StringRecord::from(self.headers)];
+        for mut row in self.rows {
+            row.resize(num_cols, String::new());
+            records.push(StringRecord::from(row));
+        }
+
+        records
+    }
+}
+
+fn flatten(
+    path: String,
+    value: &Value,
+    depth: Option<usize>,
+    array_mode: ArrayMode,
+    cells: &mut Vec<(String, String)>,
+) {
+    let can_descend = depth != Some(0);
+    let depth = depth.map(|d| d.saturating_sub(1));
+
+    match value {
+        Value::Object(object) if can_descend && !object.is_empty() => {
+            for (key, value) in object {
+                flatten(format!("{path}.{key}"), value, depth, array_mode, cells);
+            }
+        }
+        Value::Array(items)
+            if can_descend && array_mode == ArrayMode::Index && !items.is_empty() =>
+        {
+            for (i, value) in items.iter().enumerate() {
+                flatten(format!("{path}[{i}]"), value, depth, array_mode, cells);
+            }
+        }
+        _ => cells.push((path, get_cell(value, array_mode))),
+    }
+}
+
+fn get_cell(value: &Value, array_mode: ArrayMode) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if array_mode == ArrayMode::Join => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) => item.to_string(),
+                _ => get_cell(item, array_mode),
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Bool(_) | Value::Number(_) | Value::Array(_) | Value::Object(_) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RowErrorPolicy;
+    use crate::test_utils::to_rows;
+
+    const DEFAULT_CONFIG: FlattenConfig = FlattenConfig {
+        depth: None,
+        array_mode: ArrayMode::Json,
+    };
+
+    #[test]
+    fn uses_union_of_keys_as_headers() {
+        // GIVEN
+        let input = r#"[{"a": 1, "b": "x"}, {"c": true, "a": null}]"#;
+
+        // WHEN
+        let got = get_records_from_json(input.as_bytes(), &DEFAULT_CONFIG)
+            .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["a", "b", "c"],
+                vec!["1", "x", ""],
+                vec!["", "", "true"],
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_array_of_arrays_as_is() {
+        // GIVEN
+        let input = r#"[["a", "b"], [1, {"k": "v"}]]"#;
+
+        // WHEN
+        let got = get_records_from_json(input.as_bytes(), &DEFAULT_CONFIG)
+            .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![vec!["a", "b"], vec!["1", r#"{"k":"v"}"#]]
+        );
+    }
+
+    #[test]
+    fn fills_earlier_ndjson_rows_when_new_keys_appear() {
+        // GIVEN
+        let input = "{\"a\": 1}\n\n{\"b\": 2, \"a\": 3}\n{\"c\": [1, 2]}\n";
+
+        // WHEN
+        let got = get_records_from_ndjson(
+            input.as_bytes(),
+            &DEFAULT_CONFIG,
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["a", "b", "c"],
+                vec!["1", "", ""],
+                vec!["3", "2", ""],
+                vec!["", "", "[1,2]"],
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_line_number_for_invalid_ndjson() {
+        // GIVEN
+        let input = "{\"a\": 1}\n{\"a\": \n";
+
+        // WHEN
+        let got = get_records_from_ndjson(
+            input.as_bytes(),
+            &DEFAULT_CONFIG,
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect_err("input shouldn't have been parsed");
+
+        // THEN
+        assert_eq!(
+            got.to_string(),
+            "line 2, column 6: EOF while parsing a value"
+        );
+    }
+
+    #[test]
+    fn flattens_nested_objects_into_dotted_paths() {
+        // GIVEN
+        let input = r#"[{"user": {"name": "a", "address": {"city": "b"}}, "tags": ["x", "y"]}]"#;
+
+        // WHEN
+        let got = get_records_from_json(input.as_bytes(), &DEFAULT_CONFIG)
+            .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["user.name", "user.address.city", "tags"],
+                vec!["a", "b", r#"["x","y"]"#],
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_flattening_at_configured_depth() {
+        // GIVEN
+        let input = r#"[{"user": {"name": "a", "address": {"city": "b"}}}]"#;
+        let config = FlattenConfig {
+            depth: Some(1),
+            array_mode: ArrayMode::Json,
+        };
+
+        // WHEN
+        let got =
+            get_records_from_json(input.as_bytes(), &config).expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![
+                vec!["user.name", "user.address"],
+                vec!["a", r#"{"city":"b"}"#],
+            ]
+        );
+    }
+
+    #[test]
+    fn indexes_arrays_when_asked_to() {
+        // GIVEN
+        let input = r#"{"tags": ["x", {"k": "v"}]}"#;
+        let config = FlattenConfig {
+            depth: None,
+            array_mode: ArrayMode::Index,
+        };
+
+        // WHEN
+        let got = get_records_from_ndjson(
+            input.as_bytes(),
+            &config,
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![vec!["tags[0]", "tags[1].k"], vec!["x", "v"]]
+        );
+    }
+
+    #[test]
+    fn joins_arrays_when_asked_to() {
+        // GIVEN
+        let input = r#"{"tags": ["x", 1, null, [2]]}"#;
+        let config = FlattenConfig {
+            depth: None,
+            array_mode: ArrayMode::Join,
+        };
+
+        // WHEN
+        let got = get_records_from_ndjson(
+            input.as_bytes(),
+            &config,
+            &mut RowErrors::new(RowErrorPolicy::Fail),
+        )
+        .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["tags"], vec!["x, 1, , [2]"]]);
+    }
+
+    #[test]
+    fn skips_invalid_ndjson_lines_when_asked_to() {
+        // GIVEN
+        let input = "{\"a\": 1}\n{\"a\": \n[1]\n{\"a\": 2}\n";
+        let mut row_errors = RowErrors::new(RowErrorPolicy::Skip);
+
+        // WHEN
+        let got = get_records_from_ndjson(input.as_bytes(), &DEFAULT_CONFIG, &mut row_errors)
+            .expect("input should've been parsed");
+
+        // THEN
+        assert_eq!(to_rows(got), vec![vec!["a"], vec!["1"], vec!["2"]]);
+        assert_eq!(row_errors.skipped_lines(), vec![2, 3]);
+    }
+
+    #[test]
+    fn fails_for_mixed_items() {
+        // GIVEN
+        let input = r#"[{"a": 1}, [1, 2]]"#;
+
+        // WHEN
+        let got = get_records_from_json(input.as_bytes(), &DEFAULT_CONFIG);
+
+        // THEN
+        assert!(got.is_err());
+    }
+}
@@ -1,48 +1,286 @@
+mod aggregate;
+mod aligned;
+mod compression;
 mod config;
+mod delimited;
+mod describe;
+mod encoding;
+mod errors;
+mod filter;
+mod footer;
+mod inputs;
+mod json;
 mod output;
+mod sort;
+#[cfg(test)]
+mod test_utils;
+mod transform;
 mod types;
+mod values;
 
+use aggregate::{Aggregate, group_rows, parse_aggregate};
+use aligned::get_records_from_aligned_text;
 use anyhow::Context;
-use clap::Parser;
-use config::{RenderConfig, TablePadding, TableStyle};
+use clap::{Parser, Subcommand};
+use compression::read_input;
+use config::{
+    ArrayMode, Encoding, ExpandedMode, FlattenConfig, InputFormat, RaggedRowPolicy, RenderConfig,
+    RowErrorPolicy, TablePadding, TableStyle,
+};
 use csv::StringRecord;
-use output::get_output;
+use delimited::{
+    Delimiter, Dialect, detect_delimiter, get_records_from_delimited_text, parse_ascii_char,
+    parse_delimited_line,
+};
+use describe::describe;
+use encoding::decode_input;
+use errors::{RowErrors, locate_error};
+use filter::{Filter, filter_rows};
+use footer::{FooterSpec, get_footer, parse_footer_spec};
+use inputs::{Input, expand_input_paths, merge_inputs};
+use json::{get_records_from_json, get_records_from_ndjson};
+use output::{get_expanded_output, get_output};
+use sort::{SortKey, parse_sort_key, sort_rows};
 use std::fs::File;
-use std::io::BufReader;
-use types::Cols;
-
-const ROW_DELIMITER: &str = ",";
+use std::io::{BufRead, BufReader};
+use tabled::grid::util::string::get_line_width;
+use terminal_size::{Width, terminal_size};
+use transform::{
+    RowLimit, fix_ragged_rows, limit_rows, parse_rename, parse_row_range, rename_headers, transpose,
+};
+use types::{ColSelector, Cols, parse_col_selector};
 
 /// tbll outputs data in tabular format
 #[derive(Parser, Debug)]
-#[command(about, long_about=None)]
+#[command(
+    about,
+    long_about=None,
+    args_conflicts_with_subcommands = true,
+    disable_help_subcommand = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    args: Args,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Describe every column of the input instead of displaying its rows
+    ///
+    /// Every column is described by its type, number of non-empty and distinct values, smallest
+    /// and largest values, mean and median (if it's numeric), and most frequent values. Rows are
+    /// filtered via --where (and grouped via --group-by) before being described, while options
+    /// like --sort, --cols, and --head apply to the description itself.
+    Describe {
+        #[command(flatten)]
+        args: Args,
+    },
+}
+
+#[derive(clap::Args, Debug)]
 struct Args {
-    /// Input file path; tbll will read from stdin if this is not provided
+    /// Input file path or glob pattern (can be repeated to concatenate several files); tbll will
+    /// read from stdin if this is not provided
     #[arg(short = 'p', long = "input-path", value_name = "STRING")]
-    input_file_path: Option<String>,
-    /// Delimiter to use
+    input_file_paths: Vec<String>,
+    /// Add a column with this name (as the first one), holding the input each row came from
+    #[arg(long = "source-column", value_name = "STRING")]
+    source_column: Option<String>,
+    /// Drop the header line repeated by every input after the first one
+    #[arg(long = "dedupe-headers", conflicts_with = "no_header")]
+    dedupe_headers: bool,
+    /// Format of the input data
+    #[arg(short = 'f', long = "input-format", value_name = "STRING")]
+    #[clap(value_enum, default_value = "csv", value_name = "STRING")]
+    input_format: InputFormat,
+    /// Character encoding of the input; "auto" picks UTF-16 if the input starts with a UTF-16 byte
+    /// order mark, and UTF-8 otherwise
+    #[arg(short = 'e', long = "encoding", value_name = "STRING")]
+    #[clap(value_enum, default_value = "auto", value_name = "STRING")]
+    encoding: Encoding,
+    /// Replace invalid byte sequences in the input instead of failing
+    #[arg(long = "lossy")]
+    lossy: bool,
+    /// Levels of nesting to flatten into dotted column paths for JSON input; flattens
+    /// everything if not provided
+    #[arg(long = "flatten-depth", value_name = "NUMBER")]
+    flatten_depth: Option<usize>,
+    /// How to render arrays in JSON input
+    #[arg(long = "array-mode", value_name = "STRING")]
+    #[clap(value_enum, default_value = "json", value_name = "STRING")]
+    array_mode: ArrayMode,
+    /// Delimiter to use (can be more than one character); tbll will try to detect it if this is
+    /// not provided
     #[arg(short = 'd', long = "delimiter", value_name = "STRING")]
-    #[clap(default_value = ROW_DELIMITER)]
-    delimiter: char,
-    /// Command separated list of headers
+    delimiter: Option<String>,
+    /// Regular expression to split rows on (quoted fields are not supported in this mode)
+    #[arg(
+        long = "delimiter-regex",
+        value_name = "REGEX",
+        conflicts_with = "delimiter"
+    )]
+    delimiter_regex: Option<String>,
+    /// Quote character used in the input
+    #[arg(long = "quote", value_name = "CHAR", value_parser = parse_ascii_char)]
+    #[clap(default_value = "\"")]
+    quote: u8,
+    /// Escape character for quotes inside quoted fields
+    #[arg(long = "escape", value_name = "CHAR", value_parser = parse_ascii_char)]
+    escape: Option<u8>,
+    /// Don't treat two consecutive quotes inside a quoted field as an escaped quote
+    #[arg(long = "no-double-quote")]
+    no_double_quote: bool,
+    /// Skip lines starting with this character
+    #[arg(long = "comment", value_name = "CHAR", value_parser = parse_ascii_char)]
+    comment: Option<u8>,
+    /// Record terminator; CR, LF and CRLF are all treated as terminators if this is not provided
+    #[arg(long = "terminator", value_name = "CHAR", value_parser = parse_ascii_char)]
+    terminator: Option<u8>,
+    /// How to handle rows with a differing number of cells; "pad" fills missing cells, "truncate"
+    /// drops cells beyond the first row's width
+    #[arg(
+        long = "flexible",
+        value_name = "POLICY",
+        num_args = 0..=1,
+        default_missing_value = "pad"
+    )]
+    #[clap(value_enum, default_value = "error")]
+    flexible: RaggedRowPolicy,
+    /// What to do with rows that can't be parsed; "warn" skips them and reports their line numbers
+    /// after the table
+    #[arg(long = "on-error", value_name = "POLICY")]
+    #[clap(value_enum, default_value = "fail")]
+    on_error: RowErrorPolicy,
+    /// Placeholder for cells added to ragged rows
+    #[arg(long = "pad-with", value_name = "STRING")]
+    #[clap(default_value = "")]
+    pad_with: String,
+    /// Headers, separated and quoted the same way as the input; these replace the first row if
+    /// --header-row is set, and are prepended to the rows otherwise
     #[arg(long = "headers", value_name = "STRING,STRING...")]
     headers: Option<String>,
-    /// Indices of columns (starting from zero) to display
+    /// Rename a header cell (can be repeated)
+    #[arg(
+        long = "rename",
+        value_name = "OLD=NEW",
+        value_parser = parse_rename,
+        conflicts_with = "no_header"
+    )]
+    renames: Vec<(String, String)>,
+    /// Treat the first row of the input as a header
+    #[arg(long = "header-row", conflicts_with = "no_header")]
+    header_row: bool,
+    /// Treat every row as a part of the body, and render no header
+    #[arg(long = "no-header", conflicts_with = "headers")]
+    no_header: bool,
+    /// Only display the first N rows (along with the last ones, if --tail is provided as well)
+    #[arg(long = "head", value_name = "N")]
+    head: Option<usize>,
+    /// Only display the last N rows (along with the first ones, if --head is provided as well)
+    #[arg(long = "tail", value_name = "N")]
+    tail: Option<usize>,
+    /// Only display this inclusive range of rows, numbered from 1 (eg. 100-200, or 100- for all
+    /// rows from 100)
+    #[arg(
+        long = "rows",
+        value_name = "START-END",
+        value_parser = parse_row_range,
+        conflicts_with_all = ["head", "tail"]
+    )]
+    rows: Option<(usize, Option<usize>)>,
+    /// Columns to display, in the order provided (columns can be repeated); each one can be an
+    /// index (starting from zero), a negative index (-1 being the last column), an inclusive range
+    /// (eg. 0-3, or 5- for all columns from 5), or a header name
     #[arg(
         short = 'c',
         long = "cols",
-        value_name = "NUMBER,NUMBER...",
-        value_delimiter = ','
+        value_name = "COL,COL...",
+        value_delimiter = ',',
+        value_parser = parse_col_selector,
+        allow_hyphen_values = true
     )]
-    cols: Option<Vec<usize>>,
-    /// Indices of columns (starting from zero) to skip
+    cols: Option<Vec<ColSelector>>,
+    /// Columns to skip; these can be specified the same way as for --cols
     #[arg(
         short = 'C',
         long = "skip-cols",
-        value_name = "NUMBER,NUMBER...",
-        value_delimiter = ','
+        value_name = "COL,COL...",
+        value_delimiter = ',',
+        value_parser = parse_col_selector,
+        allow_hyphen_values = true
+    )]
+    skip_cols: Option<Vec<ColSelector>>,
+    /// Only display rows matching this expression (eg. 'Year >= 1995 && Genre ~ "Drama"'); columns
+    /// can be referred to by name (wrapped in backticks if needed) or index (eg. $0), compared
+    /// using ==, !=, <, <=, >, >=, matched against regexes using ~ and !~, and checked for being
+    /// empty via `== null`; conditions can be combined using &&, ||, !, and parentheses
+    #[arg(long = "where", value_name = "EXPRESSION")]
+    filter: Option<String>,
+    /// Collapse rows into one per distinct combination of values in these columns; these can be
+    /// specified the same way as for --cols
+    #[arg(
+        long = "group-by",
+        value_name = "COL,COL...",
+        value_delimiter = ',',
+        value_parser = parse_col_selector,
+        allow_hyphen_values = true,
+        conflicts_with = "no_header"
+    )]
+    group_by: Vec<ColSelector>,
+    /// Aggregates to compute for each group (or for all rows, if --group-by isn't given), out of
+    /// count, count(COL), sum(COL), avg(COL), min(COL), and max(COL) (eg. 'count,avg(Year)');
+    /// defaults to count when grouping
+    #[arg(
+        long = "agg",
+        value_name = "AGG,AGG...",
+        value_delimiter = ',',
+        value_parser = parse_aggregate,
+        allow_hyphen_values = true,
+        conflicts_with = "no_header"
+    )]
+    aggregates: Vec<Aggregate>,
+    /// Columns to sort rows by, each optionally suffixed with ":asc" or ":desc" (eg. Year:desc,0);
+    /// cells are compared as numbers, dates/times, semantic versions, or text, based on what every
+    /// cell in the column looks like
+    #[arg(
+        long = "sort",
+        value_name = "COL[:asc|:desc],...",
+        value_delimiter = ',',
+        value_parser = parse_sort_key,
+        allow_hyphen_values = true
+    )]
+    sort: Vec<SortKey>,
+    /// Ignore case when comparing text while sorting
+    #[arg(long = "ignore-case", requires = "sort")]
+    ignore_case: bool,
+    /// Functions to compute in footer rows beneath the table, out of count, sum, avg, min, and
+    /// max; each is either computed for every numeric column, or for a specific one when prefixed
+    /// with it (eg. 'Year:avg,Revenue:sum')
+    #[arg(
+        long = "footer",
+        value_name = "[COL:]FUNC,...",
+        value_delimiter = ',',
+        value_parser = parse_footer_spec,
+        allow_hyphen_values = true
+    )]
+    footer: Vec<FooterSpec>,
+    /// Swap rows and columns, so that the header becomes the first column, and every row becomes a
+    /// column of its own (rows left out via --head, --tail, or --rows aren't marked in this mode)
+    #[arg(long = "transpose")]
+    transpose: bool,
+    /// Display every row as a block of "header | value" lines (like psql's expanded display);
+    /// "auto" only does so when the table is wider than the terminal
+    #[arg(
+        long = "expanded",
+        value_name = "WHEN",
+        num_args = 0..=1,
+        default_missing_value = "always",
+        conflicts_with = "transpose"
     )]
-    skip_cols: Option<Vec<usize>>,
+    #[clap(value_enum)]
+    expanded: Option<ExpandedMode>,
     /// Border Style
     #[arg(short = 's', long = "style", value_name = "STRING")]
     #[clap(value_enum, default_value = "sharp", value_name = "STRING")]
@@ -61,70 +299,167 @@ struct Args {
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    let (args, describing) = match Cli::parse() {
+        Cli {
+            command: Some(Command::Describe { args }),
+            ..
+        } => (args, true),
+        Cli { args, .. } => (args, false),
+    };
 
     let mut data: Vec<StringRecord> = Vec::new();
 
-    let maybe_cols = match (args.cols, args.skip_cols) {
+    let maybe_selectors = match (&args.cols, &args.skip_cols) {
         (Some(_), Some(_)) => Err(anyhow::anyhow!(
             "--cols and --skip-cols cannot be used at the same time"
         )),
         (None, None) => Ok(None),
-        (Some(c), None) => Ok(Some(Cols::include(c))),
-        (None, Some(c)) => Ok(Some(Cols::skip(c))),
+        (Some(c), None) => Ok(Some((c, true))),
+        (None, Some(c)) => Ok(Some((c, false))),
     }?;
 
-    if let Some(headers) = &args.headers {
-        let headers_vec: Vec<&str> = headers.split(",").collect();
-        data.push(StringRecord::from(
-            headers_vec
-                .into_iter()
-                .map(|s| {
-                    if args.trim {
-                        s.trim().to_string()
-                    } else {
-                        s.to_string()
-                    }
-                })
-                .collect::<Vec<String>>(),
-        ));
-    }
+    let dialect = Dialect {
+        quote: args.quote,
+        escape: args.escape,
+        double_quote: !args.no_double_quote,
+        comment: args.comment,
+        terminator: args.terminator,
+        flexible: args.flexible != RaggedRowPolicy::Error,
+    };
 
-    match args.input_file_path {
-        None => {
-            let mut reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .delimiter(args.delimiter as u8)
-                .from_reader(std::io::stdin());
-
-            for result in reader.records() {
-                let record = result.context("couldn't read row from stdin")?;
-                if args.trim {
-                    data.push(get_trimmed_record(record));
-                } else {
-                    data.push(record);
-                }
-            }
-        }
-        Some(path) => {
-            let file = File::open(path)?;
-            let file_reader = BufReader::new(file);
-
-            let mut reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .delimiter(args.delimiter as u8)
-                .from_reader(file_reader);
-
-            for result in reader.records() {
-                let record = result.context("couldn't read row in file")?;
-                if args.trim {
-                    data.push(get_trimmed_record(record));
-                } else {
-                    data.push(record);
-                }
-            }
+    let paths = expand_input_paths(&args.input_file_paths)?;
+    let paths = if paths.is_empty() {
+        vec![None]
+    } else {
+        paths.iter().map(|p| Some(p.as_str())).collect()
+    };
+    let name_sources = paths.len() > 1;
+
+    let mut inputs = Vec::new();
+    let mut summaries = Vec::new();
+    let mut header_delimiter = None;
+    for path in paths {
+        let source = path.unwrap_or("stdin");
+        let input = get_input(path, &args)?;
+
+        // headers are split the same way as the (first) input, if it's delimited text
+        if header_delimiter.is_none() && args.headers.is_some() {
+            let sample = match args.input_format {
+                InputFormat::Csv => input.as_slice(),
+                _ => &[],
+            };
+            header_delimiter = Some(get_delimiter(&args, sample, &dialect)?);
         }
+
+        let mut row_errors = RowErrors::new(args.on_error);
+        let records = get_records(&input, &args, &dialect, &mut row_errors)
+            .map_err(|e| locate_error(e, source, &input))?;
+        let records = if args.trim {
+            records.into_iter().map(get_trimmed_record).collect()
+        } else {
+            records
+        };
+
+        summaries.extend(row_errors.summary(name_sources.then_some(source)));
+        inputs.push(Input {
+            source: source.to_string(),
+            records,
+        });
+    }
+
+    let input_has_header =
+        args.header_row || args.dedupe_headers || (args.headers.is_none() && !args.no_header);
+    // JSON inputs don't necessarily share the same keys, which are lined up by name instead
+    let align_columns = matches!(args.input_format, InputFormat::Json | InputFormat::Ndjson);
+    let records = merge_inputs(
+        inputs,
+        input_has_header,
+        args.dedupe_headers,
+        align_columns,
+        args.source_column.as_deref(),
+    )?;
+
+    if let (Some(headers), Some(delimiter)) = (&args.headers, &header_delimiter) {
+        let headers =
+            parse_delimited_line(headers, delimiter, &dialect).context("couldn't parse headers")?;
+        let headers = if args.trim {
+            get_trimmed_record(headers)
+        } else {
+            headers
+        };
+
+        let mut header = StringRecord::from(args.source_column.iter().collect::<Vec<_>>());
+        header.extend(headers.iter());
+        data.push(header);
+    }
+
+    // headers provided via --headers replace the ones present in the input
+    let to_skip = usize::from(input_has_header && args.headers.is_some());
+    data.extend(records.into_iter().skip(to_skip));
+
+    if let Some(header) = data.first_mut() {
+        rename_headers(header, &args.renames)?;
+    }
+
+    fix_ragged_rows(&mut data, args.flexible, &args.pad_with);
+    if let Some(expression) = &args.filter {
+        let header = data.first().filter(|_| !args.no_header);
+        let filter =
+            Filter::parse(expression, header).context("couldn't parse --where expression")?;
+        filter_rows(&mut data, &filter, !args.no_header);
+    }
+
+    if !args.group_by.is_empty() || !args.aggregates.is_empty() {
+        let aggregates = match args.aggregates.as_slice() {
+            [] => vec![Aggregate::count()],
+            aggregates => aggregates.to_vec(),
+        };
+        data = group_rows(data, &args.group_by, &aggregates)?;
+    }
+
+    // the description is a table of its own (which always has a header), and everything that
+    // follows applies to it instead of the input
+    let mut has_header = !args.no_header;
+    if describing {
+        data = describe(&data, has_header);
+        has_header = true;
+    }
+
+    sort_rows(&mut data, &args.sort, has_header, args.ignore_case)?;
+
+    // the footer covers every row, including the ones that aren't displayed
+    let footer = get_footer(&data, &args.footer, has_header)?;
+
+    let row_limit = RowLimit {
+        head: args.head,
+        tail: args.tail,
+        range: args.rows,
     };
+    let mut elisions = limit_rows(&mut data, &row_limit, has_header);
+    let mut footer_rows = footer.len();
+    data.extend(footer);
+
+    let mut maybe_cols = maybe_selectors
+        .map(|(selectors, include)| {
+            let header = data.first().filter(|_| has_header);
+            let width = data.iter().map(StringRecord::len).max().unwrap_or_default();
+            Cols::from_selectors(selectors, header, width, include)
+        })
+        .transpose()?;
+
+    // columns are selected before transposing, as they turn into rows
+    if args.transpose {
+        if let Some(cols) = maybe_cols.take() {
+            data = data
+                .iter()
+                .map(|r| cols.select(r).into_iter().collect())
+                .collect();
+        }
+        data = transpose(&data);
+        elisions.clear();
+        footer_rows = 0;
+        has_header = false;
+    }
 
     let padding = TablePadding {
         left: args.left_pad,
@@ -134,15 +469,126 @@ fn main() -> anyhow::Result<()> {
     let config = RenderConfig {
         style: args.style,
         padding,
+        header: has_header,
+        footer_rows,
+    };
+
+    let table = || get_output(&data, config, maybe_cols.as_ref(), &elisions);
+    let expanded = || {
+        get_expanded_output(
+            &data,
+            has_header,
+            maybe_cols.as_ref(),
+            &elisions,
+            footer_rows,
+        )
+    };
+    let output = match args.expanded {
+        Some(ExpandedMode::Always) => expanded(),
+        Some(ExpandedMode::Auto) => match table() {
+            Some(output) if exceeds_terminal_width(&output) => expanded(),
+            output => output,
+        },
+        None => table(),
     };
 
-    if let Some(output) = get_output(&data, config, maybe_cols) {
+    if let Some(output) = output {
         println!("{output}");
     }
 
+    for summary in summaries {
+        eprintln!("{summary}");
+    }
+
+    Ok(())
+}
+
+/// Whether output is headed to a terminal that's too narrow to fit it.
+fn exceeds_terminal_width(output: &str) -> bool {
+    let Some((Width(width), _)) = terminal_size() else {
+        return false;
+    };
+
+    output
+        .lines()
+        .any(|line| get_line_width(line) > usize::from(width))
+}
+
+/// Reads the file at the given path (or stdin if there's none), decompressing and transcoding it
+/// to UTF-8 as needed.
+fn get_input(path: Option<&str>, args: &Args) -> anyhow::Result<Vec<u8>> {
+    let (reader, source): (Box<dyn BufRead>, &str) = match path {
+        None => (Box::new(std::io::stdin().lock()), "stdin"),
+        Some(path) => {
+            let file = File::open(path)?;
+            (Box::new(BufReader::new(file)), path)
+        }
+    };
+
+    let input =
+        read_input(reader, path).with_context(|| format!("couldn't read input from {source}"))?;
+
+    decode_input(input, args.encoding, args.lossy)
+        .with_context(|| format!("couldn't decode input from {source}"))
+}
+
+fn get_delimiter(args: &Args, input: &[u8], dialect: &Dialect) -> anyhow::Result<Delimiter> {
+    match (&args.delimiter, &args.delimiter_regex) {
+        (_, Some(regex)) => Delimiter::parse_regex(regex),
+        (Some(d), None) => {
+            let delimiter = Delimiter::parse(d)?;
+            check_delimiter(&delimiter, dialect)?;
+            Ok(delimiter)
+        }
+        (None, None) => Ok(Delimiter::Byte(detect_delimiter(input, dialect))),
+    }
+}
+
+/// Makes sure the delimiter doesn't clash with the characters the csv reader treats specially.
+fn check_delimiter(delimiter: &Delimiter, dialect: &Dialect) -> anyhow::Result<()> {
+    let special_chars = [
+        ("quote", Some(dialect.quote)),
+        ("escape", dialect.escape),
+        ("comment", dialect.comment),
+        ("terminator", dialect.terminator),
+    ];
+
+    for (name, byte) in special_chars {
+        if let Some(byte) = byte
+            && delimiter.contains(byte)
+        {
+            anyhow::bail!(
+                "delimiter cannot contain the {name} character ({})",
+                char::from(byte)
+            );
+        }
+    }
+
     Ok(())
 }
 
+fn get_records(
+    input: &[u8],
+    args: &Args,
+    dialect: &Dialect,
+    row_errors: &mut RowErrors,
+) -> anyhow::Result<Vec<StringRecord>> {
+    let flatten_config = FlattenConfig {
+        depth: args.flatten_depth,
+        array_mode: args.array_mode,
+    };
+
+    match args.input_format {
+        InputFormat::Csv => {
+            let delimiter = get_delimiter(args, input, dialect)?;
+            get_records_from_delimited_text(input, &delimiter, dialect, row_errors)
+        }
+        InputFormat::Json => get_records_from_json(input, &flatten_config),
+        InputFormat::Ndjson => get_records_from_ndjson(input, &flatten_config, row_errors),
+        InputFormat::Aligned => get_records_from_aligned_text(input, row_errors),
+    }
+}
+
 fn get_trimmed_record(record: StringRecord) -> StringRecord {
     StringRecord::from(
         record
@@ -1,58 +1,49 @@
-use crate::config::RenderConfig;
-use crate::types::Cols;
+use crate::config::{RenderConfig, TableStyle};
+use crate::types::{Cols, Elision};
 use csv::StringRecord;
 use tabled::{
     builder::Builder,
-    settings::{Alignment, Padding, Style},
+    grid::util::string::get_line_width,
+    settings::{Alignment, Padding, Span, Style},
 };
 
 pub fn get_output(
     data: &[StringRecord],
     config: RenderConfig,
-    cols: Option<Cols>,
+    cols: Option<&Cols>,
+    elisions: &[Elision],
 ) -> Option<String> {
     let mut builder = Builder::default();
+    let mut elision_rows = Vec::new();
 
-    match cols {
-        Some(indices) => {
-            if indices.include {
-                data.iter().for_each(|record| {
-                    let vals = record
-                        .iter()
-                        .enumerate()
-                        .filter(|(i, _)| indices.values.contains(i))
-                        .map(|(_, s)| s)
-                        .collect::<Vec<_>>();
-                    if !vals.is_empty() {
-                        builder.push_record(vals);
-                    }
-                });
-            } else {
-                data.iter().for_each(|record| {
-                    let vals = record
-                        .iter()
-                        .enumerate()
-                        .filter(|(i, _)| !indices.values.contains(i))
-                        .map(|(_, s)| s)
-                        .collect::<Vec<_>>();
-                    if !vals.is_empty() {
-                        builder.push_record(vals);
-                    }
-                });
-            }
+    for i in 0..=data.len() {
+        for elision in elisions.iter().filter(|e| e.position == i) {
+            elision_rows.push(builder.count_records());
+            builder.push_record([get_elision_text(elision.count)]);
         }
-        None => {
-            data.iter().for_each(|record| {
-                builder.push_record(record);
-            });
+
+        let Some(record) = data.get(i) else {
+            break;
+        };
+
+        match cols {
+            Some(indices) => {
+                let vals = indices.select(record);
+                if !vals.is_empty() {
+                    builder.push_record(vals);
+                }
+            }
+            None => builder.push_record(record),
         }
     }
 
-    let mut b = builder.build();
-    if b.count_rows() == 0 {
+    // a table with nothing but elided rows isn't worth rendering
+    if builder.count_records() == elision_rows.len() {
         return None;
     }
 
+    let mut b = builder.build();
+
     b.with(Alignment::left());
     b.with(Style::sharp());
     b.with(Padding::new(
@@ -62,11 +53,147 @@ pub fn get_output(
         0,
     ));
 
+    // markdown has no notion of a footer, and would render a rule above it as a row of dashes
+    let footer_rule = !matches!(config.style, TableStyle::Markdown);
     config.style.apply_to(&mut b);
 
+    // the footer is set apart the same way the header is (whether the header is shown or not)
+    let header_line = b.get_config().get_horizontal_line(1).copied();
+    let count_rows = b.count_rows();
+    if !config.header {
+        b.get_config_mut().remove_horizontal_line(1, count_rows);
+    }
+
+    if footer_rule
+        && config.footer_rows > 0
+        && count_rows > config.footer_rows
+        && let Some(line) = header_line
+    {
+        b.get_config_mut()
+            .insert_horizontal_line(count_rows - config.footer_rows, line);
+    }
+
+    let count_columns = b.count_columns();
+    for row in elision_rows {
+        b.modify((row, 0), Span::column(count_columns as isize));
+        b.modify((row, 0), Alignment::center());
+    }
+
     Some(b.to_string())
 }
 
+/// Renders every row as a block of "header | value" lines, titled with the row's number (like
+/// psql's expanded display). Rows in the footer are numbered separately.
+pub fn get_expanded_output(
+    data: &[StringRecord],
+    header: bool,
+    cols: Option<&Cols>,
+    elisions: &[Elision],
+    footer_rows: usize,
+) -> Option<String> {
+    let select = |record| match cols {
+        Some(indices) => indices.select(record),
+        None => record.iter().collect(),
+    };
+
+    // without a header, columns are referred to by their index
+    let width = data.iter().map(StringRecord::len).max().unwrap_or_default();
+    let indices = (0..width).map(|i| i.to_string()).collect::<StringRecord>();
+    let (keys, first_row) = match data.first() {
+        Some(h) if header => (select(h), 1),
+        _ => (select(&indices), 0),
+    };
+
+    let key_width = keys
+        .iter()
+        .map(|k| get_line_width(k))
+        .max()
+        .unwrap_or_default();
+    let value_width = data[first_row..]
+        .iter()
+        .flat_map(&select)
+        .flat_map(str::lines)
+        .map(get_line_width)
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = Vec::new();
+    let mut number = 0;
+    let footer_start = data.len().saturating_sub(footer_rows).max(first_row);
+    for i in first_row..=data.len() {
+        for elision in elisions.iter().filter(|e| e.position == i) {
+            number += elision.count;
+            lines.push(get_elision_text(elision.count));
+        }
+
+        let Some(record) = data.get(i) else {
+            break;
+        };
+
+        let title = if i < footer_start {
+            number += 1;
+            format!("-[ RECORD {number} ]")
+        } else {
+            format!("-[ FOOTER {} ]", i - footer_start + 1)
+        };
+        // the rule under the title meets the column separator, unless the title is in the way
+        let title_width = get_line_width(&title);
+        if title_width < key_width + 1 {
+            lines.push(format!(
+                "{title}{}+{}",
+                "-".repeat(key_width + 1 - title_width),
+                "-".repeat(value_width + 1),
+            ));
+        } else {
+            let rule_width = (key_width + value_width + 3)
+                .saturating_sub(title_width)
+                .max(1);
+            lines.push(format!("{title}{}", "-".repeat(rule_width)));
+        }
+
+        for (j, value) in select(record).into_iter().enumerate() {
+            let key = keys.get(j).copied().unwrap_or_default();
+            // continuation lines of multi-line values are left without a key
+            for (k, line) in value
+                .lines()
+                .chain(value.is_empty().then_some(""))
+                .enumerate()
+            {
+                let key = if k == 0 { key } else { "" };
+                let padding = " ".repeat(key_width - get_line_width(key));
+                if line.is_empty() {
+                    lines.push(format!("{key}{padding} |"));
+                } else {
+                    lines.push(format!("{key}{padding} | {line}"));
+                }
+            }
+        }
+    }
+
+    if number == 0 && footer_start == data.len() {
+        return None;
+    }
+
+    Some(lines.join("\n"))
+}
+
+fn get_elision_text(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    if count == 1 {
+        "… 1 more row …".to_string()
+    } else {
+        format!("… {grouped} more rows …")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,10 +224,12 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Sharp,
             padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, None).expect("a string should've been returned");
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
 
         // THEN
         assert_snapshot!(got, @r"
@@ -120,10 +249,12 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Ascii,
             padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, None).expect("a string should've been returned");
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
 
         // THEN
         assert_snapshot!(got, @r"
@@ -144,10 +275,12 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Sharp,
             padding: TablePadding { left: 1, right: 2 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, None).expect("a string should've been returned");
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
 
         // THEN
         assert_snapshot!(got, @r"
@@ -167,10 +300,12 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Sharp,
             padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, Some(Cols::include(vec![0, 2])))
+        let got = get_output(&data, config, Some(&Cols::include(vec![0, 2])), &[])
             .expect("a string should've been returned");
 
         // THEN
@@ -184,6 +319,32 @@ mod tests {
         ");
     }
 
+    #[test]
+    fn follows_order_of_selected_indices() {
+        // GIVEN
+        let data = generate_data();
+        let config = RenderConfig {
+            style: TableStyle::Sharp,
+            padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
+        };
+
+        // WHEN
+        let got = get_output(&data, config, Some(&Cols::include(vec![2, 0, 2])), &[])
+            .expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        ┌──────────┬──────────┬──────────┐
+        │ row1col3 │ row1col1 │ row1col3 │
+        ├──────────┼──────────┼──────────┤
+        │ row2col3 │ row2col1 │ row2col3 │
+        │ row3col3 │ row3col1 │ row3col3 │
+        └──────────┴──────────┴──────────┘
+        ");
+    }
+
     #[test]
     fn skips_indices_correctly() {
         // GIVEN
@@ -191,10 +352,12 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Sharp,
             padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, Some(Cols::skip(vec![0, 2])))
+        let got = get_output(&data, config, Some(&Cols::skip(vec![0, 2])), &[])
             .expect("a string should've been returned");
 
         // THEN
@@ -215,12 +378,194 @@ mod tests {
         let config = RenderConfig {
             style: TableStyle::Sharp,
             padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
         };
 
         // WHEN
-        let got = get_output(&data, config, Some(Cols::include(vec![5, 8])));
+        let got = get_output(&data, config, Some(&Cols::include(vec![5, 8])), &[]);
 
         // THEN
         assert!(got.is_none());
     }
+
+    #[test]
+    fn renders_without_header_correctly() {
+        // GIVEN
+        let data = generate_data();
+        let config = RenderConfig {
+            style: TableStyle::Markdown,
+            padding: TablePadding { left: 1, right: 1 },
+            header: false,
+            footer_rows: 0,
+        };
+
+        // WHEN
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        | row1col1 | row1col2 | row1col3 |
+        | row2col1 | row2col2 | row2col3 |
+        | row3col1 | row3col2 | row3col3 |
+        ");
+    }
+
+    #[test]
+    fn renders_elided_rows_correctly() {
+        // GIVEN
+        let data = generate_data();
+        let config = RenderConfig {
+            style: TableStyle::Sharp,
+            padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 0,
+        };
+        let elisions = vec![Elision {
+            position: 2,
+            count: 1234,
+        }];
+
+        // WHEN
+        let got =
+            get_output(&data, config, None, &elisions).expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        ┌──────────┬──────────┬──────────┐
+        │ row1col1 │ row1col2 │ row1col3 │
+        ├──────────┼──────────┼──────────┤
+        │ row2col1 │ row2col2 │ row2col3 │
+        │      … 1,234 more rows …       │
+        │ row3col1 │ row3col2 │ row3col3 │
+        └──────────┴──────────┴──────────┘
+        ");
+    }
+
+    #[test]
+    fn renders_footer_correctly() {
+        // GIVEN
+        let data = generate_data();
+        let config = RenderConfig {
+            style: TableStyle::Sharp,
+            padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 1,
+        };
+
+        // WHEN
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        ┌──────────┬──────────┬──────────┐
+        │ row1col1 │ row1col2 │ row1col3 │
+        ├──────────┼──────────┼──────────┤
+        │ row2col1 │ row2col2 │ row2col3 │
+        ├──────────┼──────────┼──────────┤
+        │ row3col1 │ row3col2 │ row3col3 │
+        └──────────┴──────────┴──────────┘
+        ");
+    }
+
+    #[test]
+    fn renders_expanded_rows_correctly() {
+        // GIVEN
+        let data = vec![
+            StringRecord::from(vec!["Name", "Notes"]),
+            StringRecord::from(vec!["Alice", "line 1\nline 2"]),
+            StringRecord::from(vec!["Bob", ""]),
+        ];
+        let elisions = vec![Elision {
+            position: 2,
+            count: 3,
+        }];
+
+        // WHEN
+        let got = get_expanded_output(&data, true, None, &elisions, 0)
+            .expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        -[ RECORD 1 ]-
+        Name  | Alice
+        Notes | line 1
+              | line 2
+        … 3 more rows …
+        -[ RECORD 5 ]-
+        Name  | Bob
+        Notes |
+        ");
+    }
+
+    #[test]
+    fn renders_footer_without_header_correctly() {
+        // GIVEN
+        let data = vec![
+            StringRecord::from(vec!["1", "2"]),
+            StringRecord::from(vec!["sum: 1", "sum: 2"]),
+        ];
+        let config = RenderConfig {
+            style: TableStyle::Sharp,
+            padding: TablePadding { left: 1, right: 1 },
+            header: false,
+            footer_rows: 1,
+        };
+
+        // WHEN
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        ┌────────┬────────┐
+        │ 1      │ 2      │
+        ├────────┼────────┤
+        │ sum: 1 │ sum: 2 │
+        └────────┴────────┘
+        ");
+    }
+
+    #[test]
+    fn renders_footer_in_markdown_without_a_rule() {
+        // GIVEN
+        let data = vec![
+            StringRecord::from(vec!["a", "b"]),
+            StringRecord::from(vec!["1", "2"]),
+            StringRecord::from(vec!["sum: 1", "sum: 2"]),
+        ];
+        let config = RenderConfig {
+            style: TableStyle::Markdown,
+            padding: TablePadding { left: 1, right: 1 },
+            header: true,
+            footer_rows: 1,
+        };
+
+        // WHEN
+        let got = get_output(&data, config, None, &[]).expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        | a      | b      |
+        |--------|--------|
+        | 1      | 2      |
+        | sum: 1 | sum: 2 |
+        ");
+    }
+
+    #[test]
+    fn renders_expanded_rows_narrower_than_title_correctly() {
+        // GIVEN
+        let data = vec![StringRecord::from(vec!["a", "b"])];
+
+        // WHEN
+        let got = get_expanded_output(&data, false, None, &[], 0)
+            .expect("a string should've been returned");
+
+        // THEN
+        assert_snapshot!(got, @r"
+        -[ RECORD 1 ]-
+        0 | a
+        1 | b
+        ");
+    }
 }
@@ -0,0 +1,142 @@
+use crate::types::{ColSelector, parse_col_selector};
+use crate::values::CellType;
+use csv::StringRecord;
+use std::cmp::Ordering;
+
+/// A column to sort rows by, as passed to --sort.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub col: ColSelector,
+    pub descending: bool,
+}
+
+pub fn parse_sort_key(value: &str) -> Result<SortKey, String> {
+    let (col, descending) = match value.rsplit_once(':') {
+        Some((col, "asc")) => (col, false),
+        Some((col, "desc")) => (col, true),
+        _ => (value, false),
+    };
+
+    let col = parse_col_selector(col)?;
+    if let ColSelector::Range(..) = col {
+        return Err("rows can't be sorted by a range of columns".to_string());
+    }
+
+    Ok(SortKey { col, descending })
+}
+
+/// Sorts rows (leaving the header, if any, in place) by the given keys, comparing the cells of
+/// each column as per the type inferred for it. Empty cells are always placed last.
+pub fn sort_rows(
+    records: &mut [StringRecord],
+    keys: &[SortKey],
+    has_header: bool,
+    ignore_case: bool,
+) -> anyhow::Result<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let (header, rows) = match records {
+        [header, rows @ ..] if has_header => (Some(&*header), rows),
+        rows => (None, rows),
+    };
+    let width = rows.iter().map(StringRecord::len).max().unwrap_or_default();
+
+    let mut columns = Vec::new();
+    for key in keys {
+        let Some(index) = key.col.resolve(header, width)?.first().copied() else {
+            // negative indices beyond the first column don't point to anything to sort by
+            continue;
+        };
+        let cell_type = CellType::infer(rows.iter().filter_map(|r| r.get(index)));
+        columns.push((index, cell_type, key.descending));
+    }
+
+    rows.sort_by(|a, b| {
+        columns
+            .iter()
+            .map(|(index, cell_type, descending)| {
+                let a = a.get(*index).unwrap_or_default();
+                let b = b.get(*index).unwrap_or_default();
+                match (a.trim().is_empty(), b.trim().is_empty()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (false, false) => {
+                        let ordering = cell_type.compare(a, b, ignore_case);
+                        if *descending {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    }
+                }
+            })
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    #[test]
+    fn parses_sort_keys() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert_eq!(
+            parse_sort_key("Year:desc"),
+            Ok(SortKey {
+                col: ColSelector::Name("Year".to_string()),
+                descending: true
+            })
+        );
+        assert_eq!(
+            parse_sort_key("-1"),
+            Ok(SortKey {
+                col: ColSelector::FromEnd(1),
+                descending: false
+            })
+        );
+        assert!(parse_sort_key("0-2:asc").is_err());
+    }
+
+    #[test]
+    fn sorts_by_multiple_typed_columns() {
+        // GIVEN
+        let mut records = vec![
+            StringRecord::from(vec!["Movie", "Year"]),
+            StringRecord::from(vec!["Pulp Fiction", "1994"]),
+            StringRecord::from(vec!["Alien", ""]),
+            StringRecord::from(vec!["The Matrix", "1999"]),
+            StringRecord::from(vec!["Heat", "995"]),
+            StringRecord::from(vec!["Forrest Gump", "1994"]),
+        ];
+        let keys = vec![
+            parse_sort_key("Year:desc").expect("key should've been parsed"),
+            parse_sort_key("0").expect("key should've been parsed"),
+        ];
+
+        // WHEN
+        sort_rows(&mut records, &keys, true, false).expect("rows should've been sorted");
+
+        // THEN
+        assert_eq!(
+            to_rows(records),
+            vec![
+                vec!["Movie", "Year"],
+                vec!["The Matrix", "1999"],
+                vec!["Forrest Gump", "1994"],
+                vec!["Pulp Fiction", "1994"],
+                vec!["Heat", "995"],
+                vec!["Alien", ""],
+            ]
+        );
+    }
+}
@@ -0,0 +1,8 @@
+use csv::StringRecord;
+
+pub fn to_rows(records: Vec<StringRecord>) -> Vec<Vec<String>> {
+    records
+        .into_iter()
+        .map(|r| r.iter().map(String::from).collect())
+        .collect()
+}
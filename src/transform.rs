@@ -0,0 +1,318 @@
+use crate::config::RaggedRowPolicy;
+use crate::types::Elision;
+use csv::StringRecord;
+use std::ops::Range;
+
+/// Makes all records as wide as the widest one (when padding), or as wide as the first one (when
+/// truncating), filling missing cells with the placeholder.
+pub fn fix_ragged_rows(records: &mut [StringRecord], policy: RaggedRowPolicy, placeholder: &str) {
+    let width = match policy {
+        RaggedRowPolicy::Pad => records.iter().map(StringRecord::len).max(),
+        RaggedRowPolicy::Truncate => records.first().map(StringRecord::len),
+        RaggedRowPolicy::Error => return,
+    };
+    let Some(width) = width else {
+        return;
+    };
+
+    for record in records.iter_mut().filter(|r| r.len() != width) {
+        if record.len() > width {
+            record.truncate(width);
+        } else {
+            for _ in record.len()..width {
+                record.push_field(placeholder);
+            }
+        }
+    }
+}
+
+pub fn parse_rename(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((old, new)) if !old.is_empty() => Ok((old.to_string(), new.to_string())),
+        _ => Err("needs to be in the form OLD=NEW".to_string()),
+    }
+}
+
+/// Replaces header cells as per the (old, new) pairs provided; every old name needs to be present.
+pub fn rename_headers(
+    header: &mut StringRecord,
+    renames: &[(String, String)],
+) -> anyhow::Result<()> {
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    let mut cells = header.iter().map(String::from).collect::<Vec<_>>();
+    for (old, new) in renames {
+        let Some(cell) = cells.iter_mut().find(|c| *c == old) else {
+            anyhow::bail!("couldn't rename header \"{old}\" as it doesn't exist");
+        };
+        *cell = new.clone();
+    }
+
+    *header = StringRecord::from(cells);
+
+    Ok(())
+}
+
+/// Which rows to display, as per --head, --tail, and --rows.
+pub struct RowLimit {
+    pub head: Option<usize>,
+    pub tail: Option<usize>,
+    /// Inclusive range of row numbers (starting from 1); an open range extends to the last row
+    pub range: Option<(usize, Option<usize>)>,
+}
+
+pub fn parse_row_range(value: &str) -> Result<(usize, Option<usize>), String> {
+    let error = || "needs to be a range of row numbers like 100-200, or 100-".to_string();
+    let (start, end) = value.split_once('-').ok_or_else(error)?;
+    let start = start.parse::<usize>().map_err(|_| error())?;
+    if start == 0 {
+        return Err("row numbers start from 1".to_string());
+    }
+
+    if end.is_empty() {
+        return Ok((start, None));
+    }
+
+    let end = end.parse::<usize>().map_err(|_| error())?;
+    if end < start {
+        return Err(format!("range {value} ends before it starts"));
+    }
+
+    Ok((start, Some(end)))
+}
+
+/// Drops rows (other than the header, if any) that fall outside the limit, returning where the
+/// dropped runs of rows were.
+pub fn limit_rows(
+    records: &mut Vec<StringRecord>,
+    limit: &RowLimit,
+    has_header: bool,
+) -> Vec<Elision> {
+    let offset = usize::from(has_header && !records.is_empty());
+    let len = records.len() - offset;
+
+    // at most two runs of rows are kept (the head and the tail)
+    let to_keep: [Range<usize>; 2] = match (limit.range, limit.head, limit.tail) {
+        (Some((start, end)), ..) => {
+            let end = end.map_or(len, |e| e.min(len));
+            [(start - 1).min(end)..end, 0..0]
+        }
+        (None, Some(head), Some(tail)) if head.saturating_add(tail) < len => {
+            [0..head, len - tail..len]
+        }
+        (None, Some(_), Some(_)) => [0..len, 0..0],
+        (None, Some(head), None) => [0..head.min(len), 0..0],
+        (None, None, Some(tail)) => [len.saturating_sub(tail)..len, 0..0],
+        (None, None, None) => return Vec::new(),
+    };
+
+    let mut elisions = Vec::new();
+    let mut skipped = 0;
+    for (i, row) in records.split_off(offset).into_iter().enumerate() {
+        if !to_keep.iter().any(|r| r.contains(&i)) {
+            skipped += 1;
+            continue;
+        }
+
+        if skipped > 0 {
+            elisions.push(Elision {
+                position: records.len(),
+                count: skipped,
+            });
+            skipped = 0;
+        }
+        records.push(row);
+    }
+    if skipped > 0 {
+        elisions.push(Elision {
+            position: records.len(),
+            count: skipped,
+        });
+    }
+
+    elisions
+}
+
+/// Swaps rows and columns, so that the first column holds what was the first row (usually the
+/// header), and every other row becomes a column. Missing cells in ragged rows are left empty.
+pub fn transpose(records: &[StringRecord]) -> Vec<StringRecord> {
+    let width = records
+        .iter()
+        .map(StringRecord::len)
+        .max()
+        .unwrap_or_default();
+
+    (0..width)
+        .map(|i| {
+            records
+                .iter()
+                .map(|r| r.get(i).unwrap_or_default())
+                .collect::<StringRecord>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_rows;
+
+    fn generate_data() -> Vec<StringRecord> {
+        vec![
+            StringRecord::from(vec!["a", "b"]),
+            StringRecord::from(vec!["c"]),
+            StringRecord::from(vec!["d", "e", "f"]),
+        ]
+    }
+
+    #[test]
+    fn pads_rows_to_the_widest_one() {
+        // GIVEN
+        let mut data = generate_data();
+
+        // WHEN
+        fix_ragged_rows(&mut data, RaggedRowPolicy::Pad, "-");
+
+        // THEN
+        assert_eq!(
+            to_rows(data),
+            vec![
+                vec!["a", "b", "-"],
+                vec!["c", "-", "-"],
+                vec!["d", "e", "f"]
+            ]
+        );
+    }
+
+    #[test]
+    fn truncates_rows_to_the_first_one() {
+        // GIVEN
+        let mut data = generate_data();
+
+        // WHEN
+        fix_ragged_rows(&mut data, RaggedRowPolicy::Truncate, "");
+
+        // THEN
+        assert_eq!(
+            to_rows(data),
+            vec![vec!["a", "b"], vec!["c", ""], vec!["d", "e"]]
+        );
+    }
+
+    #[test]
+    fn renames_headers() {
+        // GIVEN
+        let mut header = StringRecord::from(vec!["Movie", "Year"]);
+        let renames = vec![parse_rename("Year=Released").expect("rename should've been parsed")];
+
+        // WHEN
+        rename_headers(&mut header, &renames).expect("headers should've been renamed");
+
+        // THEN
+        assert_eq!(header.iter().collect::<Vec<_>>(), vec!["Movie", "Released"]);
+    }
+
+    #[test]
+    fn fails_to_rename_absent_headers() {
+        // GIVEN
+        let mut header = StringRecord::from(vec!["Movie", "Year"]);
+        let renames = vec![("Genre".to_string(), "Category".to_string())];
+
+        // WHEN
+        let got = rename_headers(&mut header, &renames);
+
+        // THEN
+        assert_eq!(
+            got.expect_err("headers shouldn't have been renamed")
+                .to_string(),
+            r#"couldn't rename header "Genre" as it doesn't exist"#
+        );
+    }
+
+    fn generate_rows(count: usize) -> Vec<StringRecord> {
+        std::iter::once(StringRecord::from(vec!["Row"]))
+            .chain((1..=count).map(|i| StringRecord::from(vec![i.to_string()])))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_head_and_tail() {
+        // GIVEN
+        let mut records = generate_rows(10);
+        let limit = RowLimit {
+            head: Some(2),
+            tail: Some(3),
+            range: None,
+        };
+
+        // WHEN
+        let elisions = limit_rows(&mut records, &limit, true);
+
+        // THEN
+        assert_eq!(
+            to_rows(records),
+            vec![
+                vec!["Row"],
+                vec!["1"],
+                vec!["2"],
+                vec!["8"],
+                vec!["9"],
+                vec!["10"]
+            ]
+        );
+        assert_eq!(
+            elisions,
+            vec![Elision {
+                position: 3,
+                count: 5
+            }]
+        );
+    }
+
+    #[test]
+    fn keeps_range_of_rows() {
+        // GIVEN
+        let mut records = generate_rows(10);
+        let limit = RowLimit {
+            head: None,
+            tail: None,
+            range: Some(parse_row_range("4-5").expect("range should've been parsed")),
+        };
+
+        // WHEN
+        let elisions = limit_rows(&mut records, &limit, true);
+
+        // THEN
+        assert_eq!(to_rows(records), vec![vec!["Row"], vec!["4"], vec!["5"]]);
+        assert_eq!(
+            elisions,
+            vec![
+                Elision {
+                    position: 1,
+                    count: 3
+                },
+                Elision {
+                    position: 3,
+                    count: 5
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn transposes_rows_and_columns() {
+        // GIVEN
+        let data = generate_data();
+
+        // WHEN
+        let got = transpose(&data);
+
+        // THEN
+        assert_eq!(
+            to_rows(got),
+            vec![vec!["a", "c", "d"], vec!["b", "", "e"], vec!["", "", "f"]]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use csv::StringRecord;
+
 pub struct Cols {
     pub values: Vec<usize>,
     pub include: bool,
@@ -16,4 +18,184 @@ impl Cols {
             include: false,
         }
     }
+
+    /// Resolves column selectors into indices, using the header to look up names, and the width
+    /// of the table to resolve negative indices and open ranges.
+    pub fn from_selectors(
+        selectors: &[ColSelector],
+        header: Option<&StringRecord>,
+        width: usize,
+        include: bool,
+    ) -> anyhow::Result<Self> {
+        let mut values = Vec::new();
+        for selector in selectors {
+            values.extend(selector.resolve(header, width)?);
+        }
+
+        if include {
+            Ok(Self::include(values))
+        } else {
+            Ok(Self::skip(values))
+        }
+    }
+
+    /// Picks the cells of the selected columns from a record; included columns are laid out in
+    /// the order they were requested in, and can be repeated.
+    pub fn select<'a>(&self, record: &'a StringRecord) -> Vec<&'a str> {
+        if self.include {
+            self.values.iter().filter_map(|i| record.get(*i)).collect()
+        } else {
+            record
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !self.values.contains(i))
+                .map(|(_, s)| s)
+                .collect()
+        }
+    }
+}
+
+/// A column (or a range of them) referenced in --cols and --skip-cols.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColSelector {
+    /// Index starting from zero
+    Index(usize),
+    /// Index from the end; 1 is the last column
+    FromEnd(usize),
+    /// Inclusive range of indices; an open range extends to the last column
+    Range(usize, Option<usize>),
+    Name(String),
+}
+
+impl ColSelector {
+    /// Returns the indices of the columns referenced; negative indices that point before the
+    /// first column resolve to nothing.
+    pub fn resolve(
+        &self,
+        header: Option<&StringRecord>,
+        width: usize,
+    ) -> anyhow::Result<Vec<usize>> {
+        match self {
+            Self::Index(index) => Ok(vec![*index]),
+            Self::FromEnd(offset) => Ok(width.checked_sub(*offset).into_iter().collect()),
+            Self::Range(start, end) => {
+                let end = end.map_or(width, |e| e.saturating_add(1).min(width));
+                Ok((*start..end).collect())
+            }
+            Self::Name(name) => {
+                let Some(header) = header else {
+                    anyhow::bail!("columns can only be selected by name if there's a header");
+                };
+                let Some(index) = header.iter().position(|h| h == name) else {
+                    anyhow::bail!("there's no column named \"{name}\"");
+                };
+                Ok(vec![index])
+            }
+        }
+    }
+}
+
+pub fn parse_col_selector(value: &str) -> Result<ColSelector, String> {
+    if value.is_empty() {
+        return Err("column cannot be empty".to_string());
+    }
+
+    if let Ok(index) = value.parse::<usize>() {
+        return Ok(ColSelector::Index(index));
+    }
+
+    if let Some(offset) = value
+        .strip_prefix('-')
+        .and_then(|v| v.parse::<usize>().ok())
+    {
+        if offset == 0 {
+            return Err("negative indices start from -1".to_string());
+        }
+        return Ok(ColSelector::FromEnd(offset));
+    }
+
+    if let Some((start, end)) = value.split_once('-')
+        && let Ok(start) = start.parse::<usize>()
+    {
+        if end.is_empty() {
+            return Ok(ColSelector::Range(start, None));
+        }
+
+        if let Ok(end) = end.parse::<usize>() {
+            if start > end {
+                return Err(format!("range {value} ends before it starts"));
+            }
+            return Ok(ColSelector::Range(start, Some(end)));
+        }
+    }
+
+    Ok(ColSelector::Name(value.to_string()))
+}
+
+/// A run of rows left out of the output, to be marked in their place.
+#[derive(Debug, PartialEq)]
+pub struct Elision {
+    /// Index of the row (in the rows that remain) the run precedes
+    pub position: usize,
+    pub count: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_col_selectors() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert_eq!(parse_col_selector("2"), Ok(ColSelector::Index(2)));
+        assert_eq!(parse_col_selector("-1"), Ok(ColSelector::FromEnd(1)));
+        assert_eq!(
+            parse_col_selector("0-3"),
+            Ok(ColSelector::Range(0, Some(3)))
+        );
+        assert_eq!(parse_col_selector("5-"), Ok(ColSelector::Range(5, None)));
+        assert_eq!(
+            parse_col_selector("Release-Year"),
+            Ok(ColSelector::Name("Release-Year".to_string()))
+        );
+        assert!(parse_col_selector("3-1").is_err());
+        assert!(parse_col_selector("-0").is_err());
+    }
+
+    #[test]
+    fn resolves_col_selectors() {
+        // GIVEN
+        let header = StringRecord::from(vec!["Movie", "Year", "Director", "Genre", "Rating"]);
+        let selectors = vec![
+            ColSelector::Name("Genre".to_string()),
+            ColSelector::FromEnd(1),
+            ColSelector::Range(0, Some(1)),
+            ColSelector::Range(3, None),
+        ];
+
+        // WHEN
+        let got = Cols::from_selectors(&selectors, Some(&header), header.len(), true)
+            .expect("selectors should've been resolved");
+
+        // THEN
+        assert_eq!(got.values, vec![3, 4, 0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn fails_to_resolve_unknown_names() {
+        // GIVEN
+        let header = StringRecord::from(vec!["Movie", "Year"]);
+        let selectors = vec![ColSelector::Name("Genre".to_string())];
+
+        // WHEN
+        let got = Cols::from_selectors(&selectors, Some(&header), header.len(), false);
+
+        // THEN
+        assert_eq!(
+            got.err().map(|e| e.to_string()).as_deref(),
+            Some(r#"there's no column named "Genre""#)
+        );
+    }
 }
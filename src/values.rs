@@ -0,0 +1,285 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use std::cmp::Ordering;
+
+const DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%Y/%m/%d"];
+
+/// The type of the values in a column, as inferred from its non-empty cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellType {
+    Number,
+    DateTime,
+    Version,
+    Text,
+}
+
+impl CellType {
+    /// Picks the first type (in the order number, date/time, version) that every non-empty cell
+    /// conforms to, falling back to text.
+    pub fn infer<'a>(cells: impl IntoIterator<Item = &'a str>) -> Self {
+        let (mut number, mut date_time, mut version) = (true, true, true);
+        let mut is_empty = true;
+        for cell in cells.into_iter().filter(|c| !c.trim().is_empty()) {
+            is_empty = false;
+            number = number && parse_number(cell).is_some();
+            date_time = date_time && parse_date_time(cell).is_some();
+            version = version && parse_version(cell).is_some();
+            if !(number || date_time || version) {
+                return Self::Text;
+            }
+        }
+
+        match (is_empty, number, date_time, version) {
+            (true, ..) => Self::Text,
+            (_, true, ..) => Self::Number,
+            (_, _, true, _) => Self::DateTime,
+            (_, _, _, true) => Self::Version,
+            _ => Self::Text,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Number => "number",
+            Self::DateTime => "date/time",
+            Self::Version => "version",
+            Self::Text => "text",
+        }
+    }
+
+    /// Compares two cells of this type; cells that don't conform to it are compared as text.
+    pub fn compare(self, a: &str, b: &str, ignore_case: bool) -> Ordering {
+        let typed = match self {
+            Self::Number => parse_number(a)
+                .zip(parse_number(b))
+                .map(|(a, b)| a.total_cmp(&b)),
+            Self::DateTime => parse_date_time(a)
+                .zip(parse_date_time(b))
+                .map(|(a, b)| a.cmp(&b)),
+            Self::Version => parse_version(a)
+                .zip(parse_version(b))
+                .map(|(a, b)| a.cmp(&b)),
+            Self::Text => None,
+        };
+
+        typed.unwrap_or_else(|| {
+            if ignore_case {
+                compare_natural(&a.to_lowercase(), &b.to_lowercase())
+            } else {
+                compare_natural(a, b)
+            }
+        })
+    }
+}
+
+pub fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    // rules out values like "inf" and "NaN", which f64 would happily parse
+    if !value.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    value.parse::<f64>().ok()
+}
+
+/// Parses RFC 3339 timestamps (converted to UTC), and ISO 8601 like dates and date-times without
+/// an offset.
+pub fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.naive_utc());
+    }
+
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PreReleaseIdentifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+/// A semantic version; build metadata is ignored, as it doesn't affect precedence.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Version {
+    core: [u64; 3],
+    pre_release: Vec<PreReleaseIdentifier>,
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| {
+            // a version without a pre-release has a higher precedence than one with it
+            match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre_release.cmp(&other.pre_release),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim();
+    let value = value.strip_prefix(['v', 'V']).unwrap_or(value);
+    let value = value.split_once('+').map_or(value, |(v, _)| v);
+    let (core, pre_release) = match value.split_once('-') {
+        Some((core, pre_release)) => (core, Some(pre_release)),
+        None => (value, None),
+    };
+
+    let parts = core
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let core = <[u64; 3]>::try_from(parts).ok()?;
+
+    let pre_release = match pre_release {
+        None => Vec::new(),
+        Some(p) => p
+            .split('.')
+            .map(|id| match id.parse::<u64>() {
+                Ok(n) => Some(PreReleaseIdentifier::Numeric(n)),
+                Err(_) if !id.is_empty() => {
+                    Some(PreReleaseIdentifier::Alphanumeric(id.to_string()))
+                }
+                Err(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+    };
+
+    Some(Version { core, pre_release })
+}
+
+/// Compares strings such that runs of digits are compared by their numeric value (so that
+/// "file2" comes before "file10").
+fn compare_natural(a: &str, b: &str) -> Ordering {
+    let (a_chunks, b_chunks) = (get_chunks(a), get_chunks(b));
+    for (a, b) in a_chunks.iter().zip(b_chunks.iter()) {
+        let is_numeric = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        let ordering = if is_numeric(a) && is_numeric(b) {
+            let (a_digits, b_digits) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits))
+        } else {
+            a.cmp(b)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a_chunks.len().cmp(&b_chunks.len()).then_with(|| a.cmp(b))
+}
+
+/// Splits a string into alternating runs of digits and non-digits.
+fn get_chunks(value: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut prev_is_digit = None;
+    for (i, c) in value.char_indices() {
+        let is_digit = c.is_ascii_digit();
+        if prev_is_digit.is_some_and(|prev| prev != is_digit) {
+            chunks.push(&value[start..i]);
+            start = i;
+        }
+        prev_is_digit = Some(is_digit);
+    }
+    if start < value.len() {
+        chunks.push(&value[start..]);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_cell_types() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert_eq!(CellType::infer(["1", "-2.5", "", "1e3"]), CellType::Number);
+        assert_eq!(
+            CellType::infer(["2024-01-31", "2024-02-01T10:00:00Z", "2023/12/01"]),
+            CellType::DateTime
+        );
+        assert_eq!(
+            CellType::infer(["1.2.3", "v1.10.0", "2.0.0-rc.1"]),
+            CellType::Version
+        );
+        assert_eq!(CellType::infer(["1", "two"]), CellType::Text);
+        assert_eq!(CellType::infer(["", " "]), CellType::Text);
+    }
+
+    #[test]
+    fn orders_versions_by_precedence() {
+        // GIVEN
+        let mut versions = vec![
+            "1.10.0",
+            "1.2.0",
+            "1.0.0",
+            "1.0.0-rc.1",
+            "1.0.0-beta.11",
+            "1.0.0-beta.2",
+            "1.0.0-alpha",
+        ];
+
+        // WHEN
+        versions.sort_by(|a, b| CellType::Version.compare(a, b, false));
+
+        // THEN
+        assert_eq!(
+            versions,
+            vec![
+                "1.0.0-alpha",
+                "1.0.0-beta.2",
+                "1.0.0-beta.11",
+                "1.0.0-rc.1",
+                "1.0.0",
+                "1.2.0",
+                "1.10.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn orders_text_naturally() {
+        // GIVEN
+        let mut values = vec!["file10", "File2", "file2", "file1"];
+
+        // WHEN
+        let mut case_insensitive = values.clone();
+        values.sort_by(|a, b| CellType::Text.compare(a, b, false));
+        case_insensitive.sort_by(|a, b| CellType::Text.compare(a, b, true));
+
+        // THEN
+        assert_eq!(values, vec!["File2", "file1", "file2", "file10"]);
+        assert_eq!(case_insensitive, vec!["file1", "File2", "file2", "file10"]);
+    }
+}
@@ -15,28 +15,62 @@ fn shows_help() {
 
     // WHEN
     // THEN
-    assert_cmd_snapshot!(cmd, @r"
+    assert_cmd_snapshot!(cmd, @r#"
     success: true
     exit_code: 0
     ----- stdout -----
     tbll outputs data in tabular format
 
     Usage: tbll [OPTIONS]
+           tbll <COMMAND>
+
+    Commands:
+      describe  Describe every column of the input instead of displaying its rows
 
     Options:
-      -p, --input-path <STRING>           Input file path; tbll will read from stdin if this is not provided
-      -d, --delimiter <STRING>            Delimiter to use [default: ,]
-          --headers <STRING,STRING...>    Command separated list of headers
-      -c, --cols <NUMBER,NUMBER...>       Indices of columns (starting from zero) to display
-      -C, --skip-cols <NUMBER,NUMBER...>  Indices of columns (starting from zero) to skip
-      -s, --style <STRING>                Border Style [default: sharp] [possible values: ascii, ascii-rounded, blank, dots, empty, extended, markdown, modern, modern-rounded, psql, re-structured-text, rounded, sharp]
-      -l, --left-pad <NUMBER>             Left padding for cells [default: 1]
-      -r, --right-pad <NUMBER>            Right padding for cells [default: 1]
-      -t, --trim                          Trim whitespace from cells
-      -h, --help                          Print help
+      -p, --input-path <STRING>         Input file path or glob pattern (can be repeated to concatenate several files); tbll will read from stdin if this is not provided
+          --source-column <STRING>      Add a column with this name (as the first one), holding the input each row came from
+          --dedupe-headers              Drop the header line repeated by every input after the first one
+      -f, --input-format <STRING>       Format of the input data [default: csv] [possible values: csv, json, ndjson, aligned]
+      -e, --encoding <STRING>           Character encoding of the input; "auto" picks UTF-16 if the input starts with a UTF-16 byte order mark, and UTF-8 otherwise [default: auto] [possible values: auto, utf-8, utf-16le, utf-16be, latin1, windows-1252]
+          --lossy                       Replace invalid byte sequences in the input instead of failing
+          --flatten-depth <NUMBER>      Levels of nesting to flatten into dotted column paths for JSON input; flattens everything if not provided
+          --array-mode <STRING>         How to render arrays in JSON input [default: json] [possible values: join, index, json]
+      -d, --delimiter <STRING>          Delimiter to use (can be more than one character); tbll will try to detect it if this is not provided
+          --delimiter-regex <REGEX>     Regular expression to split rows on (quoted fields are not supported in this mode)
+          --quote <CHAR>                Quote character used in the input [default: "]
+          --escape <CHAR>               Escape character for quotes inside quoted fields
+          --no-double-quote             Don't treat two consecutive quotes inside a quoted field as an escaped quote
+          --comment <CHAR>              Skip lines starting with this character
+          --terminator <CHAR>           Record terminator; CR, LF and CRLF are all treated as terminators if this is not provided
+          --flexible [<POLICY>]         How to handle rows with a differing number of cells; "pad" fills missing cells, "truncate" drops cells beyond the first row's width [default: error] [possible values: pad, truncate, error]
+          --on-error <POLICY>           What to do with rows that can't be parsed; "warn" skips them and reports their line numbers after the table [default: fail] [possible values: skip, warn, fail]
+          --pad-with <STRING>           Placeholder for cells added to ragged rows [default: ""]
+          --headers <STRING,STRING...>  Headers, separated and quoted the same way as the input; these replace the first row if --header-row is set, and are prepended to the rows otherwise
+          --rename <OLD=NEW>            Rename a header cell (can be repeated)
+          --header-row                  Treat the first row of the input as a header
+          --no-header                   Treat every row as a part of the body, and render no header
+          --head <N>                    Only display the first N rows (along with the last ones, if --tail is provided as well)
+          --tail <N>                    Only display the last N rows (along with the first ones, if --head is provided as well)
+          --rows <START-END>            Only display this inclusive range of rows, numbered from 1 (eg. 100-200, or 100- for all rows from 100)
+      -c, --cols <COL,COL...>           Columns to display, in the order provided (columns can be repeated); each one can be an index (starting from zero), a negative index (-1 being the last column), an inclusive range (eg. 0-3, or 5- for all columns from 5), or a header name
+      -C, --skip-cols <COL,COL...>      Columns to skip; these can be specified the same way as for --cols
+          --where <EXPRESSION>          Only display rows matching this expression (eg. 'Year >= 1995 && Genre ~ "Drama"'); columns can be referred to by name (wrapped in backticks if needed) or index (eg. $0), compared using ==, !=, <, <=, >, >=, matched against regexes using ~ and !~, and checked for being empty via `== null`; conditions can be combined using &&, ||, !, and parentheses
+          --group-by <COL,COL...>       Collapse rows into one per distinct combination of values in these columns; these can be specified the same way as for --cols
+          --agg <AGG,AGG...>            Aggregates to compute for each group (or for all rows, if --group-by isn't given), out of count, count(COL), sum(COL), avg(COL), min(COL), and max(COL) (eg. 'count,avg(Year)'); defaults to count when grouping
+          --sort <COL[:asc|:desc],...>  Columns to sort rows by, each optionally suffixed with ":asc" or ":desc" (eg. Year:desc,0); cells are compared as numbers, dates/times, semantic versions, or text, based on what every cell in the column looks like
+          --ignore-case                 Ignore case when comparing text while sorting
+          --footer <[COL:]FUNC,...>     Functions to compute in footer rows beneath the table, out of count, sum, avg, min, and max; each is either computed for every numeric column, or for a specific one when prefixed with it (eg. 'Year:avg,Revenue:sum')
+          --transpose                   Swap rows and columns, so that the header becomes the first column, and every row becomes a column of its own (rows left out via --head, --tail, or --rows aren't marked in this mode)
+          --expanded [<WHEN>]           Display every row as a block of "header | value" lines (like psql's expanded display); "auto" only does so when the table is wider than the terminal [possible values: always, auto]
+      -s, --style <STRING>              Border Style [default: sharp] [possible values: ascii, ascii-rounded, blank, dots, empty, extended, markdown, modern, modern-rounded, psql, re-structured-text, rounded, sharp]
+      -l, --left-pad <NUMBER>           Left padding for cells [default: 1]
+      -r, --right-pad <NUMBER>          Right padding for cells [default: 1]
+      -t, --trim                        Trim whitespace from cells
+      -h, --help                        Print help
 
     ----- stderr -----
-    ");
+    "#);
 }
 
 #[test]
@@ -373,127 +407,1808 @@ fn trimming_applies_to_custom_headers_as_well() {
     ");
 }
 
-//------------//
-//  FAILURES  //
-//------------//
+#[test]
+fn reading_json_array_of_objects_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.json",
+        "--input-format",
+        "json",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────────────────┬──────┬────────────────────────┬───────────────────┐
+    │ Movie                          │ Year │ Director               │ Genre             │
+    ├────────────────────────────────┼──────┼────────────────────────┼───────────────────┤
+    │ The Matrix                     │ 1999 │ Lana & Lilly Wachowski │                   │
+    │ Pulp Fiction                   │ 1994 │                        │ Crime             │
+    │ The Good, the Bad and the Ugly │ 1967 │ Sergio Leone           │ Spaghetti Western │
+    └────────────────────────────────┴──────┴────────────────────────┴───────────────────┘
+
+    ----- stderr -----
+    ");
+}
 
 #[test]
-fn fails_if_input_file_is_non_existent() {
+fn reading_json_array_of_arrays_works() {
     // GIVEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["--input-path", "tests/data/nonexistent.txt"]);
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-2.json",
+        "--input-format",
+        "json",
+    ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    ┌──────────────┬──────┬────────────────────────┐
+    │ Movie        │ Year │ Director               │
+    ├──────────────┼──────┼────────────────────────┤
+    │ The Matrix   │ 1999 │ Lana & Lilly Wachowski │
+    │ Pulp Fiction │ 1994 │                        │
+    └──────────────┴──────┴────────────────────────┘
 
     ----- stderr -----
-    Error: No such file or directory (os error 2)
     ");
 }
 
 #[test]
-fn fails_if_style_is_not_supported() {
+fn reading_ndjson_works() {
     // GIVEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/nonexistent.txt",
-        "--style",
-        "blah",
+        "tests/data/input-1.ndjson",
+        "--input-format",
+        "ndjson",
     ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    ┌───────┬────────────────┬──────┬─────────┬────────────┬───────┐
+    │ level │ msg            │ port │ path    │ latency_ms │ retry │
+    ├───────┼────────────────┼──────┼─────────┼────────────┼───────┤
+    │ info  │ server started │ 8080 │         │            │       │
+    │ warn  │ slow request   │      │ /movies │ 1250       │       │
+    │ error │ db unreachable │      │         │            │ true  │
+    └───────┴────────────────┴──────┴─────────┴────────────┴───────┘
 
     ----- stderr -----
-    error: invalid value 'blah' for '--style <STRING>'
-      [possible values: ascii, ascii-rounded, blank, dots, empty, extended, markdown, modern, modern-rounded, psql, re-structured-text, rounded, sharp]
+    ");
+}
 
-      tip: a similar value exists: 'blank'
+#[test]
+fn flattening_nested_json_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-nested.ndjson",
+        "--input-format",
+        "ndjson",
+        "--array-mode",
+        "index",
+    ]);
 
-    For more information, try '--help'.
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────┬───────────┬───────────────────┬─────────┬─────────┬──────────────────┐
+    │ id │ user.name │ user.address.city │ tags[0] │ tags[1] │ user.address.zip │
+    ├────┼───────────┼───────────────────┼─────────┼─────────┼──────────────────┤
+    │ 1  │ alice     │ Berlin            │ admin   │ ops     │                  │
+    │ 2  │ bob       │ Lisbon            │ dev     │         │ 1000             │
+    └────┴───────────┴───────────────────┴─────────┴─────────┴──────────────────┘
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_left_pad_is_not_a_number() {
+fn limiting_flatten_depth_works() {
     // GIVEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/nonexistent.txt",
-        "--left-pad",
-        "blah",
+        "tests/data/input-nested.ndjson",
+        "--input-format",
+        "ndjson",
+        "--flatten-depth",
+        "1",
+        "--array-mode",
+        "join",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────┬───────────┬────────────────────────────────┬────────────┐
+    │ id │ user.name │ user.address                   │ tags       │
+    ├────┼───────────┼────────────────────────────────┼────────────┤
+    │ 1  │ alice     │ {"city":"Berlin"}              │ admin, ops │
+    │ 2  │ bob       │ {"city":"Lisbon","zip":"1000"} │ dev        │
+    └────┴───────────┴────────────────────────────────┴────────────┘
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn reading_aligned_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-aligned.txt",
+        "--input-format",
+        "aligned",
     ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    ┌────────────────────────┬───────┬──────────────────┬─────────────┬─────┐
+    │ NAME                   │ READY │ STATUS           │ RESTARTS    │ AGE │
+    ├────────────────────────┼───────┼──────────────────┼─────────────┼─────┤
+    │ api-7d9f8b6c5d-2xkqz   │ 1/1   │ Running          │ 0           │ 3d  │
+    │ worker-5c7b9d8f6-lmn4p │ 0/1   │ CrashLoopBackOff │ 12 (5m ago) │ 3d  │
+    │ redis-0                │ 1/1   │ Running          │ 0           │ 10d │
+    └────────────────────────┴───────┴──────────────────┴─────────────┴─────┘
 
     ----- stderr -----
-    error: invalid value 'blah' for '--left-pad <NUMBER>': invalid digit found in string
+    ");
+}
 
-    For more information, try '--help'.
+#[test]
+fn detecting_delimiter_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-6.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────────────────┬──────┬────────────────────────┬───────────────────┐
+    │ Movie                          │ Year │ Director               │ Genre             │
+    ├────────────────────────────────┼──────┼────────────────────────┼───────────────────┤
+    │ The Matrix                     │ 1999 │ Lana & Lilly Wachowski │ Science Fiction   │
+    │ The Good, the Bad and the Ugly │ 1967 │ Sergio Leone           │ Spaghetti Western │
+    └────────────────────────────────┴──────┴────────────────────────┴───────────────────┘
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_right_pad_is_not_a_number() {
+fn using_multi_character_delimiter_works() {
     // GIVEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/nonexistent.txt",
-        "--left-pad",
-        "blah",
+        "tests/data/input-7.txt",
+        "--delimiter",
+        " │ ",
     ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    ┌──────────────┬──────┬────────────────────────┐
+    │ Movie        │ Year │ Director               │
+    ├──────────────┼──────┼────────────────────────┤
+    │ The Matrix   │ 1999 │ Lana & Lilly Wachowski │
+    │ Pulp Fiction │ 1994 │ Quentin Tarantino      │
+    └──────────────┴──────┴────────────────────────┘
 
     ----- stderr -----
-    error: invalid value 'blah' for '--left-pad <NUMBER>': invalid digit found in string
+    ");
+}
 
-    For more information, try '--help'.
+#[test]
+fn using_escaped_tab_delimiter_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--delimiter", r"\t"]);
+    let input = "Movie\tYear\nThe Matrix\t1999\n";
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(input), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────┬──────┐
+    │ Movie      │ Year │
+    ├────────────┼──────┤
+    │ The Matrix │ 1999 │
+    └────────────┴──────┘
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_both_cols_and_skip_cols_are_provided() {
+fn using_regex_delimiter_works() {
     // GIVEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/input-1.txt",
-        "--cols",
-        "0,3",
-        "--skip-cols",
-        "1,2",
+        "tests/data/input-aligned.txt",
+        "--delimiter-regex",
+        r"\s{2,}",
     ]);
 
     // WHEN
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    ┌────────────────────────┬───────┬──────────────────┬─────────────┬─────┐
+    │ NAME                   │ READY │ STATUS           │ RESTARTS    │ AGE │
+    ├────────────────────────┼───────┼──────────────────┼─────────────┼─────┤
+    │ api-7d9f8b6c5d-2xkqz   │ 1/1   │ Running          │ 0           │ 3d  │
+    │ worker-5c7b9d8f6-lmn4p │ 0/1   │ CrashLoopBackOff │ 12 (5m ago) │ 3d  │
+    │ redis-0                │ 1/1   │ Running          │ 0           │ 10d │
+    └────────────────────────┴───────┴──────────────────┴─────────────┴─────┘
 
     ----- stderr -----
-    Error: --cols and --skip-cols cannot be used at the same time
+    ");
+}
+
+#[test]
+fn using_custom_quote_and_comment_characters_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-8.txt",
+        "--quote",
+        "'",
+        "--comment",
+        "#",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────────────────┬──────┬──────────────────┐
+    │ Movie                          │ Year │ Director         │
+    ├────────────────────────────────┼──────┼──────────────────┤
+    │ The Good, the Bad and the Ugly │ 1967 │ Sergio Leone     │
+    │ Schindler's List               │ 1993 │ Steven Spielberg │
+    └────────────────────────────────┴──────┴──────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn padding_ragged_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-ragged.txt",
+        "--flexible",
+        "--pad-with",
+        "-",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────┬───────────────────┬───────┐
+    │ Movie        │ Year │ Director          │ -     │
+    ├──────────────┼──────┼───────────────────┼───────┤
+    │ The Matrix   │ 1999 │ -                 │ -     │
+    │ Pulp Fiction │ 1994 │ Quentin Tarantino │ Crime │
+    └──────────────┴──────┴───────────────────┴───────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn truncating_ragged_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-ragged.txt",
+        "--flexible",
+        "truncate",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────┬───────────────────┐
+    │ Movie        │ Year │ Director          │
+    ├──────────────┼──────┼───────────────────┤
+    │ The Matrix   │ 1999 │                   │
+    │ Pulp Fiction │ 1994 │ Quentin Tarantino │
+    └──────────────┴──────┴───────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn skipping_malformed_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-malformed.txt",
+        "--on-error",
+        "warn",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┐
+    │ Movie                    │ Year │ Director               │
+    ├──────────────────────────┼──────┼────────────────────────┤
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │
+    └──────────────────────────┴──────┴────────────────────────┘
+
+    ----- stderr -----
+    warning: skipped 2 row(s) that couldn't be parsed (lines: 3, 4)
+    ");
+}
+
+#[test]
+fn reading_utf16_input_with_bom_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-utf16le.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────────┬──────┬────────────────────┐
+    │ Movie                  │ Year │ Director           │
+    ├────────────────────────┼──────┼────────────────────┤
+    │ Amélie                 │ 2001 │ Jean-Pierre Jeunet │
+    │ Léon: The Professional │ 1994 │ Luc Besson         │
+    └────────────────────────┴──────┴────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn reading_windows_1252_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-windows-1252.txt",
+        "--encoding",
+        "windows-1252",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────┬──────┬────────────────────┐
+    │ Movie              │ Year │ Director           │
+    ├────────────────────┼──────┼────────────────────┤
+    │ Amélie             │ 2001 │ Jean-Pierre Jeunet │
+    │ “Crocodile” Dundee │ 1986 │ Peter Faiman       │
+    └────────────────────┴──────┴────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn replacing_invalid_utf8_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-invalid-utf8.txt",
+        "--lossy",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────┬──────┐
+    │ Movie      │ Year │
+    ├────────────┼──────┤
+    │ The Matrix │ 1999 │
+    │ Am�lie     │ 2001 │
+    └────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn reading_gzip_compressed_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-compressed.txt.gz"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┬─────────────────┐
+    │ Movie                    │ Year │ Director               │ Genre           │
+    ├──────────────────────────┼──────┼────────────────────────┼─────────────────┤
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │ Science Fiction │
+    │ Pulp Fiction             │ 1994 │ Quentin Tarantino      │ Crime           │
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │ Drama           │
+    └──────────────────────────┴──────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn reading_bzip2_and_xz_compressed_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut bzip2_cmd = fx.cmd(["--input-path", "tests/data/input-compressed.txt.bz2"]);
+    let mut xz_cmd = fx.cmd(["--input-path", "tests/data/input-compressed.txt.xz"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(bzip2_cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┬─────────────────┐
+    │ Movie                    │ Year │ Director               │ Genre           │
+    ├──────────────────────────┼──────┼────────────────────────┼─────────────────┤
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │ Science Fiction │
+    │ Pulp Fiction             │ 1994 │ Quentin Tarantino      │ Crime           │
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │ Drama           │
+    └──────────────────────────┴──────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+    assert_cmd_snapshot!(xz_cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┬─────────────────┐
+    │ Movie                    │ Year │ Director               │ Genre           │
+    ├──────────────────────────┼──────┼────────────────────────┼─────────────────┤
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │ Science Fiction │
+    │ Pulp Fiction             │ 1994 │ Quentin Tarantino      │ Crime           │
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │ Drama           │
+    └──────────────────────────┴──────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn detecting_compressed_stdin_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--cols", "0,1"]);
+    let input = std::fs::read("tests/data/input-compressed.txt.zst").expect("fixture should exist");
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(input), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┐
+    │ Movie                    │ Year │
+    ├──────────────────────────┼──────┤
+    │ The Matrix               │ 1999 │
+    │ Pulp Fiction             │ 1994 │
+    │ The Shawshank Redemption │ 1994 │
+    └──────────────────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn concatenating_multiple_inputs_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--input-path",
+        "tests/data/input-part-2.txt",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────┐
+    │ Movie        │ Year │
+    ├──────────────┼──────┤
+    │ The Matrix   │ 1999 │
+    │ Pulp Fiction │ 1994 │
+    │ Movie        │ Year │
+    │ Amélie       │ 2001 │
+    └──────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn concatenating_inputs_matched_by_glob_with_source_column_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-*.txt",
+        "--source-column",
+        "File",
+        "--dedupe-headers",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌─────────────────────────────┬──────────────┬──────┐
+    │ File                        │ Movie        │ Year │
+    ├─────────────────────────────┼──────────────┼──────┤
+    │ tests/data/input-part-1.txt │ The Matrix   │ 1999 │
+    │ tests/data/input-part-1.txt │ Pulp Fiction │ 1994 │
+    │ tests/data/input-part-2.txt │ Amélie       │ 2001 │
+    └─────────────────────────────┴──────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn concatenating_ndjson_inputs_with_different_keys_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-format",
+        "ndjson",
+        "--input-path",
+        "tests/data/input-part-*.ndjson",
+        "--source-column",
+        "File",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────────────────────────┬────────────┬──────┬───────┐
+    │ File                           │ Movie      │ Year │ Genre │
+    ├────────────────────────────────┼────────────┼──────┼───────┤
+    │ tests/data/input-part-1.ndjson │ The Matrix │ 1999 │       │
+    │ tests/data/input-part-2.ndjson │ Amélie     │ 2001 │ Drama │
+    └────────────────────────────────┴────────────┴──────┴───────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn replacing_header_row_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--header-row",
+        "--headers",
+        "Title,Released",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────────┐
+    │ Title        │ Released │
+    ├──────────────┼──────────┤
+    │ The Matrix   │ 1999     │
+    │ Pulp Fiction │ 1994     │
+    └──────────────┴──────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn rendering_without_header_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--no-header"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("The Matrix,1999\nPulp Fiction,1994\n"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────┐
+    │ The Matrix   │ 1999 │
+    │ Pulp Fiction │ 1994 │
+    └──────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn parsing_headers_like_the_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--headers", r#""Title, original";Year"#]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("The Matrix;1999\nPulp Fiction;1994\n"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌─────────────────┬──────┐
+    │ Title, original │ Year │
+    ├─────────────────┼──────┤
+    │ The Matrix      │ 1999 │
+    │ Pulp Fiction    │ 1994 │
+    └─────────────────┴──────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn renaming_headers_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--rename",
+        "Movie=Title",
+        "--rename",
+        "Year=Released",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────────┐
+    │ Title        │ Released │
+    ├──────────────┼──────────┤
+    │ The Matrix   │ 1999     │
+    │ Pulp Fiction │ 1994     │
+    └──────────────┴──────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn selecting_columns_by_name_and_negative_index_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--cols",
+        "Movie,-1",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬─────────────────┐
+    │ Movie                    │ Genre           │
+    ├──────────────────────────┼─────────────────┤
+    │ The Matrix               │ Science Fiction │
+    │ Pulp Fiction             │ Crime           │
+    │ The Shawshank Redemption │ Drama           │
+    └──────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn skipping_a_range_of_columns_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--skip-cols",
+        "1-2",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬─────────────────┐
+    │ Movie                    │ Genre           │
+    ├──────────────────────────┼─────────────────┤
+    │ The Matrix               │ Science Fiction │
+    │ Pulp Fiction             │ Crime           │
+    │ The Shawshank Redemption │ Drama           │
+    └──────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn reordering_and_repeating_columns_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--cols",
+        "0,3,1,0",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬─────────────────┬──────┬──────────────────────────┐
+    │ Movie                    │ Genre           │ Year │ Movie                    │
+    ├──────────────────────────┼─────────────────┼──────┼──────────────────────────┤
+    │ The Matrix               │ Science Fiction │ 1999 │ The Matrix               │
+    │ Pulp Fiction             │ Crime           │ 1994 │ Pulp Fiction             │
+    │ The Shawshank Redemption │ Drama           │ 1994 │ The Shawshank Redemption │
+    └──────────────────────────┴─────────────────┴──────┴──────────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn sorting_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--sort",
+        "Year,Movie:desc",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┬─────────────────┐
+    │ Movie                    │ Year │ Director               │ Genre           │
+    ├──────────────────────────┼──────┼────────────────────────┼─────────────────┤
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │ Drama           │
+    │ Pulp Fiction             │ 1994 │ Quentin Tarantino      │ Crime           │
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │ Science Fiction │
+    └──────────────────────────┴──────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn sorting_rows_by_version_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--sort", "-1:desc"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("Crate,Version\ntbll,1.10.0\ntbll,1.2.0\ntbll,1.10.0-rc.1\n"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌───────┬─────────────┐
+    │ Crate │ Version     │
+    ├───────┼─────────────┤
+    │ tbll  │ 1.10.0      │
+    │ tbll  │ 1.10.0-rc.1 │
+    │ tbll  │ 1.2.0       │
+    └───────┴─────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn filtering_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--where",
+        r#"Year < 1999 && Genre !~ "^Cr""#,
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────┬───────┐
+    │ Movie                    │ Year │ Director       │ Genre │
+    ├──────────────────────────┼──────┼────────────────┼───────┤
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont │ Drama │
+    └──────────────────────────┴──────┴────────────────┴───────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn limiting_rows_to_head_and_tail_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--head", "2", "--tail", "1"]);
+    let input = std::iter::once("Row,Square".to_string())
+        .chain((1..=1500).map(|i| format!("{i},{}", i * i)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(input), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌─────────┬───────────┐
+    │ Row     │ Square    │
+    ├─────────┼───────────┤
+    │ 1       │ 1         │
+    │ 2       │ 4         │
+    │ … 1,497 more rows … │
+    │ 1500    │ 2250000   │
+    └─────────┴───────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn limiting_rows_to_a_range_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--rows", "2-2"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────┬──────┬───────────────────┬───────┐
+    │ Movie        │ Year │ Director          │ Genre │
+    ├──────────────┼──────┼───────────────────┼───────┤
+    │                 … 1 more row …                  │
+    │ Pulp Fiction │ 1994 │ Quentin Tarantino │ Crime │
+    │                 … 1 more row …                  │
+    └──────────────┴──────┴───────────────────┴───────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn grouping_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--group-by",
+        "Year",
+        "--agg",
+        "count,min(Movie),max(Movie)",
+        "--sort",
+        "count:desc",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────┬───────┬──────────────┬──────────────────────────┐
+    │ Year │ count │ min(Movie)   │ max(Movie)               │
+    ├──────┼───────┼──────────────┼──────────────────────────┤
+    │ 1994 │ 2     │ Pulp Fiction │ The Shawshank Redemption │
+    │ 1999 │ 1     │ The Matrix   │ The Matrix               │
+    └──────┴───────┴──────────────┴──────────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn aggregating_without_grouping_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--agg",
+        "count,sum(Year),avg(Year)",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌───────┬───────────┬───────────┐
+    │ count │ sum(Year) │ avg(Year) │
+    ├───────┼───────────┼───────────┤
+    │ 3     │ 5987      │ 1995.6667 │
+    └───────┴───────────┴───────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn aggregating_filtered_out_rows_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--where", "a > 5", "--agg", "count,sum(a)"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("a\n1\n"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌───────┬────────┐
+    │ count │ sum(a) │
+    ├───────┼────────┤
+    │ 0     │ 0      │
+    └───────┴────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn rendering_footer_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--footer",
+        "sum,Movie:count,Year:max",
+        "--head",
+        "1",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────────┬───────────┬────────────────────────┬─────────────────┐
+    │ Movie      │ Year      │ Director               │ Genre           │
+    ├────────────┼───────────┼────────────────────────┼─────────────────┤
+    │ The Matrix │ 1999      │ Lana & Lilly Wachowski │ Science Fiction │
+    │                          … 2 more rows …                          │
+    ├────────────┼───────────┼────────────────────────┼─────────────────┤
+    │            │ sum: 5987 │                        │                 │
+    │ count: 3   │           │                        │                 │
+    │            │ max: 1999 │                        │                 │
+    └────────────┴───────────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn rendering_footer_labels_rows_with_numeric_first_column() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--footer", "sum,avg,max"]);
+    let input = "cpu,mem\n1,2\n3,4\n";
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(input), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌────────┬────────┐
+    │ cpu    │ mem    │
+    ├────────┼────────┤
+    │ 1      │ 2      │
+    │ 3      │ 4      │
+    ├────────┼────────┤
+    │ sum: 4 │ sum: 6 │
+    │ avg: 2 │ avg: 3 │
+    │ max: 3 │ max: 4 │
+    └────────┴────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn describing_input_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["describe", "--input-path", "tests/data/input-1.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────┬────────┬───────────┬──────────┬────────────────┬──────────────────────────┬───────────┬────────┬───────────────────────────────────────────────────────────────────────┐
+    │ Column   │ Type   │ Non-empty │ Distinct │ Min            │ Max                      │ Mean      │ Median │ Most frequent                                                         │
+    ├──────────┼────────┼───────────┼──────────┼────────────────┼──────────────────────────┼───────────┼────────┼───────────────────────────────────────────────────────────────────────┤
+    │ Movie    │ text   │ 3         │ 3        │ Pulp Fiction   │ The Shawshank Redemption │           │        │ The Matrix (1), Pulp Fiction (1), The Shawshank Redemption (1)        │
+    │ Year     │ number │ 3         │ 2        │ 1994           │ 1999                     │ 1995.6667 │ 1994   │ 1994 (2), 1999 (1)                                                    │
+    │ Director │ text   │ 3         │ 3        │ Frank Darabont │ Quentin Tarantino        │           │        │ Lana & Lilly Wachowski (1), Quentin Tarantino (1), Frank Darabont (1) │
+    │ Genre    │ text   │ 3         │ 3        │ Crime          │ Science Fiction          │           │        │ Science Fiction (1), Crime (1), Drama (1)                             │
+    └──────────┴────────┴───────────┴──────────┴────────────────┴──────────────────────────┴───────────┴────────┴───────────────────────────────────────────────────────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn selecting_columns_of_description_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "describe",
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--where",
+        "Year < 1995",
+        "--cols",
+        "Column,Non-empty,Min",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────┬───────────┬────────────────┐
+    │ Column   │ Non-empty │ Min            │
+    ├──────────┼───────────┼────────────────┤
+    │ Movie    │ 2         │ Pulp Fiction   │
+    │ Year     │ 2         │ 1994           │
+    │ Director │ 2         │ Frank Darabont │
+    │ Genre    │ 2         │ Crime          │
+    └──────────┴───────────┴────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn transposing_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--transpose"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────┬────────────────────────┬───────────────────┬──────────────────────────┐
+    │ Movie    │ The Matrix             │ Pulp Fiction      │ The Shawshank Redemption │
+    │ Year     │ 1999                   │ 1994              │ 1994                     │
+    │ Director │ Lana & Lilly Wachowski │ Quentin Tarantino │ Frank Darabont           │
+    │ Genre    │ Science Fiction        │ Crime             │ Drama                    │
+    └──────────┴────────────────────────┴───────────────────┴──────────────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn transposing_respects_selected_columns() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--transpose",
+        "--cols",
+        "Genre,Movie",
+        "--head",
+        "1",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌───────┬─────────────────┐
+    │ Genre │ Science Fiction │
+    │ Movie │ The Matrix      │
+    └───────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn expanded_display_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--expanded",
+        "--skip-cols",
+        "Director",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    -[ RECORD 1 ]-------------------
+    Movie | The Matrix
+    Year  | 1999
+    Genre | Science Fiction
+    -[ RECORD 2 ]-------------------
+    Movie | Pulp Fiction
+    Year  | 1994
+    Genre | Crime
+    -[ RECORD 3 ]-------------------
+    Movie | The Shawshank Redemption
+    Year  | 1994
+    Genre | Drama
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn automatic_expanded_display_falls_back_to_table_without_terminal() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--expanded",
+        "auto",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────────────────────┬──────┬────────────────────────┬─────────────────┐
+    │ Movie                    │ Year │ Director               │ Genre           │
+    ├──────────────────────────┼──────┼────────────────────────┼─────────────────┤
+    │ The Matrix               │ 1999 │ Lana & Lilly Wachowski │ Science Fiction │
+    │ Pulp Fiction             │ 1994 │ Quentin Tarantino      │ Crime           │
+    │ The Shawshank Redemption │ 1994 │ Frank Darabont         │ Drama           │
+    └──────────────────────────┴──────┴────────────────────────┴─────────────────┘
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn using_double_quote_as_delimiter_with_custom_quote_works() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--quote", "'", "--delimiter", "\""]);
+    let input = "Movie\"Year\n'Heat \"95'\"1995\n";
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(input), @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    ┌──────────┬──────┐
+    │ Movie    │ Year │
+    ├──────────┼──────┤
+    │ Heat "95 │ 1995 │
+    └──────────┴──────┘
+
+    ----- stderr -----
+    "#);
+}
+
+//------------//
+//  FAILURES  //
+//------------//
+
+#[test]
+fn fails_if_input_file_is_non_existent() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/nonexistent.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: No such file or directory (os error 2)
+    ");
+}
+
+#[test]
+fn fails_if_style_is_not_supported() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/nonexistent.txt",
+        "--style",
+        "blah",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'blah' for '--style <STRING>'
+      [possible values: ascii, ascii-rounded, blank, dots, empty, extended, markdown, modern, modern-rounded, psql, re-structured-text, rounded, sharp]
+
+      tip: a similar value exists: 'blank'
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_left_pad_is_not_a_number() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/nonexistent.txt",
+        "--left-pad",
+        "blah",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'blah' for '--left-pad <NUMBER>': invalid digit found in string
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_right_pad_is_not_a_number() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/nonexistent.txt",
+        "--left-pad",
+        "blah",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'blah' for '--left-pad <NUMBER>': invalid digit found in string
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_both_cols_and_skip_cols_are_provided() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--cols",
+        "0,3",
+        "--skip-cols",
+        "1,2",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: --cols and --skip-cols cannot be used at the same time
+    ");
+}
+
+#[test]
+fn fails_if_json_input_is_not_an_array() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-format", "json"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin(r#"{"Movie": "The Matrix"}"#), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: JSON input needs to be an array of objects or an array of arrays
+    ");
+}
+
+#[test]
+fn fails_if_ndjson_line_is_not_an_object() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-format", "ndjson"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("{\"a\": 1}\n[1, 2]\n"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse stdin at line 2: expected a JSON object
+
+      2 | [1, 2]
+    ");
+}
+
+#[test]
+fn fails_if_delimiter_is_empty() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--delimiter", ""]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: delimiter cannot be empty
+    ");
+}
+
+#[test]
+fn fails_if_quote_is_not_a_single_ascii_character() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--quote", "«"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '«' for '--quote <CHAR>': needs to be a single ASCII character
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_rows_are_ragged() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-ragged.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse tests/data/input-ragged.txt at line 2, byte 20: found 2 field(s), but 3 were expected (use --flexible to allow rows with a differing number of fields)
+
+      2 | The Matrix,1999
+    ");
+}
+
+#[test]
+fn fails_with_location_if_json_is_malformed() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-format", "json"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("[\n  {\"Movie\": \"The Matrix\"},\n  {\"Movie\" \"Pulp Fiction\"}\n]\n"), @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse stdin at line 3, column 12: expected `:`
+
+      3 |   {"Movie" "Pulp Fiction"}
+    "#);
+}
+
+#[test]
+fn fails_with_location_if_row_is_not_valid_utf8() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-invalid-utf8.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse tests/data/input-invalid-utf8.txt at line 3, byte 27: field 1 is not valid UTF-8
+
+      3 | Am�lie,2001
+    ");
+}
+
+#[test]
+fn fails_if_glob_matches_no_files() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/absent-*.txt"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: no files match the pattern tests/data/absent-*.txt
+    ");
+}
+
+#[test]
+fn fails_to_dedupe_differing_headers() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--input-path",
+        "tests/data/input-6.txt",
+        "--dedupe-headers",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: can't de-duplicate headers: the first line of tests/data/input-6.txt doesn't match that of tests/data/input-part-1.txt
+    ");
+}
+
+#[test]
+fn fails_if_renamed_header_does_not_exist() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--rename",
+        "Genre=Category",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't rename header "Genre" as it doesn't exist
+    "#);
+}
+
+#[test]
+fn fails_if_selected_column_name_does_not_exist() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-part-1.txt",
+        "--cols",
+        "Genre",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: there's no column named "Genre"
+    "#);
+}
+
+#[test]
+fn fails_if_where_expression_is_invalid() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--where",
+        "Year >= ",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse --where expression
+
+    Caused by:
+        unexpected end of expression at position 8
+    ");
+}
+
+#[test]
+fn fails_if_aggregate_cant_be_computed() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--group-by",
+        "Year",
+        "--agg",
+        "sum(Genre)",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't compute sum(Genre): "Science Fiction" is not a number
+    "#);
+}
+
+#[test]
+fn fails_if_aggregate_is_invalid() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--agg",
+        "median(Year)",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'median(Year)' for '--agg <AGG,AGG...>': unknown function "median" (supported: count, sum, avg, min, max)
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_footer_function_is_invalid() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--footer",
+        "Year:median",
+    ]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'Year:median' for '--footer <[COL:]FUNC,...>': unknown function "median" (supported: count, sum, avg, min, max)
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_options_precede_describe() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "describe"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the subcommand 'describe' cannot be used with '--input-path <STRING>'
+
+    Usage: tbll [OPTIONS]
+           tbll <COMMAND>
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_delimiter_contains_quote_character() {
+    // GIVEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--quote", "'", "--delimiter", "'"]);
+
+    // WHEN
+    // THEN
+    assert_cmd_snapshot!(cmd.pass_stdin("a'b\n"), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: delimiter cannot contain the quote character (')
     ");
 }